@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use crate::export::RunRecord;
+use crate::{build_maze, get_algorithm_info, reference_path, BenchmarkPlan, Config, PathfindingResult};
+
+// Summary statistics over a set of samples.
+struct Summary {
+    mean: f64,
+    median: f64,
+    stddev: f64,
+    min: f64,
+    max: f64,
+    p95: f64,
+}
+
+impl Summary {
+    fn of(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        } else {
+            sorted[n / 2]
+        };
+        // Sample standard deviation (n - 1), zero for a single sample.
+        let stddev = if n > 1 {
+            (sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+        // Nearest-rank percentile.
+        let p95 = sorted[((0.95 * n as f64).ceil() as usize).clamp(1, n) - 1];
+
+        Self { mean, median, stddev, min: sorted[0], max: sorted[n - 1], p95 }
+    }
+}
+
+// Every sample gathered for one algorithm across all mazes and repetitions.
+#[derive(Default)]
+struct Samples {
+    time_us: Vec<f64>,
+    steps: Vec<f64>,
+    reexpanded: Vec<f64>,
+    frontier_peak: Vec<f64>,
+    path_len: Vec<f64>,
+    path_cost: Vec<f64>,
+    // Percent above the reference, BFS path length or Dijkstra path cost on terrain, for runs on
+    // mazes where that algorithm gave one.
+    excess: Vec<f64>,
+    not_found: usize,
+    // Runs among `not_found` that stopped at their expansion budget.
+    gave_up: usize,
+}
+
+// Runs every configured algorithm on `plan.mazes` mazes, `plan.repetitions` times each, and
+// prints summary statistics. Maze `i` is generated from seed `base + i`, so any single maze
+// can be replayed with `--seed`. Each algorithm gets one untimed warm-up run per maze.
+// Every timed run is also returned as a record for export.
+pub fn run(config: &Config, plan: BenchmarkPlan) -> Vec<RunRecord> {
+    let base_seed = config.resolve_seed();
+    let mut records = Vec::new();
+    let mut samples: Vec<Samples> = config.algorithms_to_run.iter().map(|_| Samples::default()).collect();
+    let mut weighted = false;
+
+    for maze_index in 0..plan.mazes {
+        let seed = base_seed.wrapping_add(maze_index as u64);
+        let maze = build_maze(config, seed);
+        weighted |= maze.is_weighted();
+        let reference = reference_path(config, &maze);
+        for (algo, samples) in config.algorithms_to_run.iter().zip(samples.iter_mut()) {
+            let info = get_algorithm_info(*algo);
+            info.pathfinder.search(&maze);
+            for repetition in 0..plan.repetitions {
+                let outcome = info.pathfinder.search(&maze);
+                let result = PathfindingResult::new(&info, &outcome, &maze, reference);
+                records.push(RunRecord::new(config, seed, &result, repetition));
+                samples.time_us.push(duration_us(outcome.elapsed));
+                samples.steps.push(outcome.expanded as f64);
+                samples.reexpanded.push(outcome.reexpanded as f64);
+                samples.frontier_peak.push(outcome.frontier_peak as f64);
+                if outcome.found {
+                    samples.path_len.push(outcome.path.len() as f64);
+                    samples.path_cost.push(result.path_cost as f64);
+                    samples.excess.extend(result.excess());
+                } else {
+                    samples.not_found += 1;
+                    samples.gave_up += usize::from(outcome.gave_up);
+                }
+            }
+        }
+    }
+
+    println!("Benchmark:       {} mazes x {} repetitions", plan.mazes, plan.repetitions);
+    println!("Maze Type:       {}", config.maze_type_label());
+    if let Some(generator) = config.generator_label() {
+        println!("Generator:       {}", generator);
+    }
+    if config.maze_is_fixed() {
+        // Every maze is the same loaded one, whatever its seed.
+        println!("Seed:            {}", config.seed_label(base_seed));
+    } else {
+        println!("Seeds:           {}..={}", base_seed, base_seed.wrapping_add(plan.mazes as u64 - 1));
+    }
+    println!("Maze Dimensions: {}x{}", config.maze_width, config.maze_height);
+
+    for (algo, samples) in config.algorithms_to_run.iter().zip(&samples) {
+        let info = get_algorithm_info(*algo);
+        println!();
+        println!("{} ({} runs)", info.name, samples.time_us.len());
+        println!(
+            "  {:<15} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "Metric", "Mean", "Median", "StdDev", "Min", "Max", "P95"
+        );
+        print_summary_row("Time (us)", &samples.time_us);
+        print_summary_row("Steps", &samples.steps);
+        if samples.reexpanded.iter().any(|&count| count > 0.0) {
+            print_summary_row("Re-expanded", &samples.reexpanded);
+        }
+        print_summary_row("Frontier Peak", &samples.frontier_peak);
+        print_summary_row("Path Len", &samples.path_len);
+        if weighted {
+            print_summary_row("Path Cost", &samples.path_cost);
+        }
+        if !samples.excess.is_empty() {
+            print_summary_row(if weighted { "vs Dijkstra (%)" } else { "vs BFS (%)" }, &samples.excess);
+        }
+        if samples.not_found > 0 {
+            println!("  No path found in {} runs", samples.not_found);
+        }
+        if samples.gave_up > 0 {
+            println!("  Gave up at the expansion budget in {} of them", samples.gave_up);
+        }
+    }
+    records
+}
+
+fn duration_us(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1000.0
+}
+
+fn print_summary_row(label: &str, samples: &[f64]) {
+    if samples.is_empty() {
+        println!("  {:<15} {:>12}", label, "-");
+        return;
+    }
+    let s = Summary::of(samples);
+    println!(
+        "  {:<15} {:>12.3} {:>12.3} {:>12.3} {:>12.3} {:>12.3} {:>12.3}",
+        label, s.mean, s.median, s.stddev, s.min, s.max, s.p95
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(samples: &[f64]) -> [f64; 6] {
+        let s = Summary::of(samples);
+        [s.mean, s.median, s.stddev, s.min, s.max, s.p95]
+    }
+
+    #[test]
+    fn summarizes_odd_and_even_sample_counts() {
+        assert_eq!(summary(&[5.0, 1.0, 3.0]), [3.0, 3.0, 2.0, 1.0, 5.0, 5.0]);
+        let [mean, median, stddev, min, max, p95] = summary(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!([mean, median, min, max, p95], [2.5, 2.5, 1.0, 4.0, 4.0]);
+        assert!((stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        // The 95th percentile of 1..=20 is the 19th value, not an interpolation.
+        let ramp: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert_eq!(summary(&ramp)[5], 19.0);
+    }
+
+    #[test]
+    fn single_sample_has_no_spread() {
+        assert_eq!(summary(&[7.5]), [7.5, 7.5, 0.0, 7.5, 7.5, 7.5]);
+    }
+}
@@ -0,0 +1,297 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crc32fast::Hasher;
+
+use crate::terrain::Terrain;
+use crate::Maze;
+
+// Compact binary maze files for large grids and archived corpora. All integers are little-endian.
+//
+// | bytes | field                                                     |
+// |-------|-----------------------------------------------------------|
+// | 8     | magic `KNOSMAZE`                                          |
+// | 1     | version, currently 1 (see `VERSION`)                      |
+// | 4 + 4 | width, height                                             |
+// | 4 + 4 | start x, y                                                |
+// | 4 + 4 | end x, y                                                  |
+// | 1 + 8 | 1 if a seed follows, then the seed (0 when absent)        |
+// | 1     | 1 if a terrain section follows                            |
+// | ...   | cells, row-major, two bits each: bit 0 east, bit 1 south  |
+// | ...   | optional terrain, two bits per cell (`Terrain::code`)     |
+// | 4     | CRC-32 of everything above                                |
+//
+// The wall bits are the in-memory layout of `Maze::walls`: every other wall is the east or
+// south wall of a neighbour, and the outer border is always closed. Bits are packed LSB first
+// and each section is padded to a whole byte.
+const MAGIC: &[u8; 8] = b"KNOSMAZE";
+// Bumped whenever the layout changes; files with any other version are rejected.
+const VERSION: u8 = 1;
+// Bytes from the magic to the terrain flag.
+const HEADER_LEN: u64 = 43;
+
+// Passes every byte written through to `inner` and into the running checksum.
+struct ChecksumWriter<W> {
+    inner: W,
+    hasher: Hasher,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct ChecksumReader<R> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+// Packs `bits` LSB first into whole bytes and writes them, one buffered chunk at a time.
+fn write_bits(writer: &mut impl Write, bits: impl Iterator<Item = bool>) -> io::Result<()> {
+    let mut chunk = Vec::with_capacity(4096);
+    let mut byte = 0u8;
+    let mut filled = 0;
+    for bit in bits {
+        byte |= (bit as u8) << filled;
+        filled += 1;
+        if filled == 8 {
+            chunk.push(byte);
+            (byte, filled) = (0, 0);
+            if chunk.len() == chunk.capacity() {
+                writer.write_all(&chunk)?;
+                chunk.clear();
+            }
+        }
+    }
+    if filled > 0 {
+        chunk.push(byte);
+    }
+    writer.write_all(&chunk)
+}
+
+// Reads `count` bits written by `write_bits`, handing each to `visit` with its index.
+fn read_bits(reader: &mut impl Read, count: usize, mut visit: impl FnMut(usize, bool)) -> io::Result<()> {
+    let mut chunk = [0u8; 4096];
+    let mut index = 0;
+    while index < count {
+        let bytes = (count - index).div_ceil(8).min(chunk.len());
+        reader.read_exact(&mut chunk[..bytes])?;
+        for &byte in &chunk[..bytes] {
+            for bit in 0..8 {
+                if index < count {
+                    visit(index, (byte >> bit) & 1 == 1);
+                    index += 1;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<usize> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+// Streams `maze` to `writer` without building the whole encoding in memory.
+pub fn write(writer: impl Write, maze: &Maze, seed: Option<u64>) -> Result<(), String> {
+    let to_u32 = |value: usize| u32::try_from(value).map_err(|_| format!("{} does not fit the binary format", value));
+    let mut writer = ChecksumWriter { inner: writer, hasher: Hasher::new() };
+    let mut header = Vec::with_capacity(HEADER_LEN as usize);
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    for value in [maze.width, maze.height, maze.start_point.0, maze.start_point.1, maze.end_point.0, maze.end_point.1] {
+        header.extend_from_slice(&to_u32(value)?.to_le_bytes());
+    }
+    header.push(seed.is_some() as u8);
+    header.extend_from_slice(&seed.unwrap_or(0).to_le_bytes());
+    header.push(maze.is_weighted() as u8);
+
+    let io_error = |e: io::Error| e.to_string();
+    writer.write_all(&header).map_err(io_error)?;
+    write_bits(&mut writer, (0..2 * maze.width * maze.height).map(|bit| maze.walls.get(bit))).map_err(io_error)?;
+    if maze.is_weighted() {
+        let codes = maze.terrain.iter().flat_map(|terrain| [terrain.code() & 1 == 1, terrain.code() & 2 == 2]);
+        write_bits(&mut writer, codes).map_err(io_error)?;
+    }
+
+    let checksum = writer.hasher.clone().finalize();
+    writer.inner.write_all(&checksum.to_le_bytes()).map_err(io_error)?;
+    writer.flush().map_err(io_error)
+}
+
+// Size in bytes of a complete file for a `width` x `height` maze.
+fn file_len(width: usize, height: usize, has_terrain: bool) -> u64 {
+    let section = (2 * width as u64 * height as u64).div_ceil(8);
+    HEADER_LEN + section + if has_terrain { section } else { 0 } + 4
+}
+
+// Streams a maze back from `reader`, which holds `len` bytes, returning it and its seed if one
+// was stored. Fails on a wrong magic or version, an out-of-range endpoint, a length that does
+// not match the size in the header, or a checksum mismatch. The length is checked before the
+// maze is allocated, so a corrupt header cannot ask for gigabytes.
+pub fn read(reader: impl Read, len: u64) -> Result<(Maze, Option<u64>), String> {
+    let mut reader = ChecksumReader { inner: reader, hasher: Hasher::new() };
+    let io_error = |e: io::Error| match e.kind() {
+        io::ErrorKind::UnexpectedEof => "file is truncated".to_string(),
+        _ => e.to_string(),
+    };
+
+    let mut magic = [0; 9];
+    reader.read_exact(&mut magic).map_err(io_error)?;
+    if &magic[..8] != MAGIC {
+        return Err("not a binary maze file".to_string());
+    }
+    if magic[8] != VERSION {
+        return Err(format!("unsupported binary maze version {}", magic[8]));
+    }
+
+    let mut fields = [0; 6];
+    for field in fields.iter_mut() {
+        *field = read_u32(&mut reader).map_err(io_error)?;
+    }
+    let [width, height, start_x, start_y, end_x, end_y] = fields;
+    if width.checked_mul(height).and_then(|cells| cells.checked_mul(2)).is_none() {
+        return Err(format!("maze size {}x{} is too large", width, height));
+    }
+    if width == 0 || height == 0 {
+        return Err(format!("maze size {}x{} must be at least 1x1", width, height));
+    }
+    if start_x >= width || start_y >= height || end_x >= width || end_y >= height {
+        return Err(format!("start or end point lies outside the {}x{} maze", width, height));
+    }
+    let mut seed_bytes = [0; 10];
+    reader.read_exact(&mut seed_bytes).map_err(io_error)?;
+    let seed = (seed_bytes[0] == 1).then(|| u64::from_le_bytes(seed_bytes[1..9].try_into().unwrap()));
+    let has_terrain = seed_bytes[9] == 1;
+    let expected_len = file_len(width, height, has_terrain);
+    if len < expected_len {
+        return Err("file is truncated".to_string());
+    }
+    if len > expected_len {
+        return Err(format!("file is {} bytes, {} more than a {}x{} maze needs", len, len - expected_len, width, height));
+    }
+
+    let mut maze = Maze::new(width, height);
+    maze.start_point = (start_x, start_y);
+    maze.end_point = (end_x, end_y);
+    read_bits(&mut reader, 2 * width * height, |bit, wall| maze.walls.set(bit, wall)).map_err(io_error)?;
+    if has_terrain {
+        let mut codes = vec![0u8; width * height];
+        read_bits(&mut reader, 2 * width * height, |bit, set| codes[bit / 2] |= (set as u8) << (bit % 2))
+            .map_err(io_error)?;
+        for (i, code) in codes.into_iter().enumerate() {
+            let terrain = Terrain::from_code(code).ok_or_else(|| format!("invalid terrain code {}", code))?;
+            maze.set_terrain((i % width, i / width), terrain);
+        }
+    }
+
+    let expected = reader.hasher.clone().finalize();
+    let mut checksum = [0; 4];
+    reader.inner.read_exact(&mut checksum).map_err(io_error)?;
+    if u32::from_le_bytes(checksum) != expected {
+        return Err("checksum mismatch, the file is corrupted".to_string());
+    }
+    Ok((maze, seed))
+}
+
+pub fn load(path: &Path) -> Result<(Maze, Option<u64>), String> {
+    let file = File::open(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let len = file.metadata().map_err(|e| format!("cannot read {}: {}", path.display(), e))?.len();
+    read(BufReader::new(file), len).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn save(path: &Path, maze: &Maze, seed: Option<u64>) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    write(BufWriter::new(file), maze, seed).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_maze;
+
+    fn encode(maze: &Maze, seed: Option<u64>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write(&mut bytes, maze, seed).unwrap();
+        bytes
+    }
+
+    fn read_error(bytes: &[u8]) -> String {
+        read(bytes, bytes.len() as u64).err().expect("the file was accepted")
+    }
+
+    #[test]
+    fn round_trips_walls_terrain_endpoints_and_seed() {
+        for (maze, seed) in [(test_maze(23, 17, true), Some(42)), (test_maze(5, 3, false), None)] {
+            let bytes = encode(&maze, seed);
+            assert_eq!(bytes.len() as u64, file_len(maze.width, maze.height, maze.is_weighted()));
+            let (loaded, loaded_seed) = read(&bytes[..], bytes.len() as u64).unwrap();
+            assert_eq!(loaded_seed, seed);
+            assert_eq!(loaded.is_weighted(), maze.is_weighted());
+            assert_eq!(loaded.to_text(seed), maze.to_text(seed));
+        }
+    }
+
+    #[test]
+    fn rejects_a_flipped_byte() {
+        let bytes = encode(&test_maze(23, 17, true), Some(42));
+        // One byte in each section: the seed, walls, terrain and the checksum itself. A
+        // corrupted terrain byte may also be caught as an invalid code before the checksum.
+        for index in [36, HEADER_LEN as usize + 3, bytes.len() - 20, bytes.len() - 1] {
+            let mut corrupted = bytes.clone();
+            corrupted[index] ^= 0x10;
+            let message = read_error(&corrupted);
+            assert!(
+                message == "checksum mismatch, the file is corrupted" || message.starts_with("invalid terrain code"),
+                "byte {}: {}",
+                index,
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_padded_files() {
+        let bytes = encode(&test_maze(9, 6, false), None);
+        for len in [5, HEADER_LEN as usize, bytes.len() - 1] {
+            assert_eq!(read_error(&bytes[..len]), "file is truncated");
+        }
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert_eq!(read_error(&padded), "file is 62 bytes, 1 more than a 9x6 maze needs");
+    }
+
+    #[test]
+    fn rejects_other_versions_and_formats() {
+        let mut bytes = encode(&test_maze(9, 6, false), None);
+        bytes[8] = 2;
+        assert_eq!(read_error(&bytes), "unsupported binary maze version 2");
+        bytes[0] = b'X';
+        assert_eq!(read_error(&bytes), "not a binary maze file");
+    }
+
+    #[test]
+    fn checks_the_length_before_allocating_a_huge_maze() {
+        let mut bytes = encode(&test_maze(9, 6, false), None);
+        bytes[9..17].copy_from_slice(&[0xFF, 0xFF, 0, 0, 0xFF, 0xFF, 0, 0]);
+        assert_eq!(read_error(&bytes), "file is truncated");
+    }
+}
@@ -0,0 +1,29 @@
+// A fixed-size set of bits packed into 64-bit words.
+#[derive(Clone)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    // `len` bits, all set to `value`.
+    pub fn new(len: usize, value: bool) -> Self {
+        Self { words: vec![if value { !0 } else { 0 }; len.div_ceil(64)] }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        (self.words[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        let mask = 1 << (index % 64);
+        if value {
+            self.words[index / 64] |= mask;
+        } else {
+            self.words[index / 64] &= !mask;
+        }
+    }
+
+    pub fn fill(&mut self, value: bool) {
+        self.words.fill(if value { !0 } else { 0 });
+    }
+}
@@ -0,0 +1,382 @@
+use std::path::PathBuf;
+
+use clap::builder::RangedU64ValueParser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+
+use crate::experiment;
+use crate::export::ExportTargets;
+use crate::svg::SvgLayer;
+use crate::{
+    Algorithm, BenchmarkPlan, Config, DynamicPlan, Generator, Hand, Heuristic, LoadedMaze, MazeFormat, MAX_GENERATED_CELLS,
+};
+
+// Every field is optional so anything left out falls back to `Config::default()`.
+#[derive(Parser)]
+#[command(name = "knossos", about = "Generate mazes and compare pathfinding algorithms")]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Maze width in cells
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    width: Option<usize>,
+
+    /// Maze height in cells
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    height: Option<usize>,
+
+    /// Generate a perfect maze (no loops) instead of one with extra openings
+    #[arg(long)]
+    perfect: bool,
+
+    /// Fraction of the cell count removed as extra walls in imperfect mazes, between 0 and 1
+    #[arg(long)]
+    loop_percentage: Option<f32>,
+
+    /// Algorithm used to carve the maze
+    #[arg(long, value_enum)]
+    generator: Option<Generator>,
+
+    /// Start cell as `X,Y` instead of the top-left corner
+    #[arg(long, value_name = "X,Y", value_parser = parse_point)]
+    start: Option<(usize, usize)>,
+
+    /// End cell as `X,Y` instead of the bottom-right corner
+    #[arg(long, value_name = "X,Y", value_parser = parse_point)]
+    end: Option<(usize, usize)>,
+
+    /// Paint noise-shaped mud and water regions that cost more to cross
+    #[arg(long)]
+    terrain: bool,
+
+    /// Comma-separated algorithms to run in order, e.g. `bfs,dfs,astar-euclidean`
+    #[arg(long = "algo", value_name = "ALGOS", value_delimiter = ',', value_parser = parse_algorithm)]
+    algorithms: Option<Vec<Algorithm>>,
+
+    /// Seed for every random choice, to replay a previous run
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Never open a window; print the results table to stdout
+    #[arg(long)]
+    headless: bool,
+
+    /// Skip the search animations and go straight to the stats screen
+    #[arg(long)]
+    skip_visualization: bool,
+
+    /// Cells drawn per animation frame
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    batch_size: Option<usize>,
+
+    /// Target frames per second for the window
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    fps: Option<u64>,
+
+    /// Window width in pixels
+    #[arg(long)]
+    screen_width: Option<usize>,
+
+    /// Window height in pixels
+    #[arg(long)]
+    screen_height: Option<usize>,
+
+    /// Save a PNG of the maze, explored cells and final path for every run into this directory
+    #[arg(long)]
+    png_dir: Option<PathBuf>,
+
+    /// Pixels per maze cell in exported PNGs
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(2..))]
+    png_cell_size: Option<usize>,
+
+    /// Record every search animation as a GIF into this directory
+    #[arg(long)]
+    gif_dir: Option<PathBuf>,
+
+    /// Pixels per maze cell in recorded GIFs
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(2..))]
+    gif_cell_size: Option<usize>,
+
+    /// Save the maze as SVG into this directory (one file per run if `--svg-layers` has explored or path)
+    #[arg(long)]
+    svg_dir: Option<PathBuf>,
+
+    /// Comma-separated layers drawn over the SVG walls
+    #[arg(long, value_enum, value_delimiter = ',')]
+    svg_layers: Option<Vec<SvgLayer>>,
+
+    /// Save every maze into this directory, as `.maze` text or `.mazb` binary depending on `--maze-format`
+    #[arg(long)]
+    maze_dir: Option<PathBuf>,
+
+    /// File format for `--maze-dir`
+    #[arg(long, value_enum)]
+    maze_format: Option<MazeFormat>,
+
+    /// Run on the maze in this `.maze`, `.mazb`, `.png` or `.pbm` file instead of generating one
+    #[arg(long, conflicts_with_all = ["width", "height", "perfect", "loop_percentage", "generator", "experiment"])]
+    load_maze: Option<PathBuf>,
+
+    /// Image pixels per maze cell when `--load-maze` is a PNG or PBM
+    #[arg(long, requires = "load_maze", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    image_cell_size: Option<usize>,
+
+    /// Write one CSV row per algorithm run to this file
+    #[arg(long)]
+    export_csv: Option<PathBuf>,
+
+    /// Write every algorithm run as a JSON array to this file
+    #[arg(long)]
+    export_json: Option<PathBuf>,
+
+    /// TOML or JSON file listing mazes and algorithms to run; overrides the maze and algorithm flags
+    #[arg(long)]
+    experiment: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Same as `--headless`
+    Headless,
+    /// Run every algorithm on many mazes, several times each, and print summary statistics
+    Bench {
+        /// Number of mazes, generated from consecutive seeds
+        #[arg(long, default_value_t = 10, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        mazes: usize,
+
+        /// Timed runs of each algorithm on every maze
+        #[arg(long, default_value_t = 5, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        repetitions: usize,
+    },
+    /// Walk an agent through the maze while walls open and close, comparing D* Lite replanning with full searches
+    Dynamic {
+        /// Walls opened or closed at every change
+        #[arg(long, default_value_t = 20, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        toggles: usize,
+
+        /// Agent steps between changes
+        #[arg(long, default_value_t = 5, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        interval: usize,
+    },
+}
+
+const ALGORITHM_NAMES: &str = "bfs, bidirectional-bfs, dfs, dijkstra, astar, astar-manhattan, astar-euclidean, astar-chebyshev, astar-zero, weighted-astar, weighted-astar=W, greedy, iddfs, idastar, jps, wall-follower-left, wall-follower-right, pledge, tremaux, dead-end-filling, dfs-explorer, dfs-explorer=R, frontier-explorer, frontier-explorer=R";
+
+// Weight used by `weighted-astar` when none is given.
+const DEFAULT_ASTAR_WEIGHT: f64 = 2.0;
+
+pub fn parse_algorithm(name: &str) -> Result<Algorithm, String> {
+    let name = name.trim().to_ascii_lowercase();
+    if let Some(weight) = name.strip_prefix("weighted-astar=") {
+        return match weight.parse::<f64>() {
+            Ok(weight) if weight.is_finite() && weight >= 1.0 => Ok(Algorithm::WeightedAStar(weight)),
+            _ => Err(format!("invalid A* weight `{}` (expected a number of at least 1)", weight)),
+        };
+    }
+    for (prefix, explorer) in [
+        ("dfs-explorer=", Algorithm::DfsExplorer as fn(usize) -> Algorithm),
+        ("frontier-explorer=", Algorithm::FrontierExplorer),
+    ] {
+        if let Some(radius) = name.strip_prefix(prefix) {
+            return radius
+                .parse::<usize>()
+                .map(explorer)
+                .map_err(|_| format!("invalid sensor radius `{}` (expected a whole number of cells)", radius));
+        }
+    }
+    match name.as_str() {
+        "bfs" => Ok(Algorithm::Bfs),
+        "bidirectional-bfs" => Ok(Algorithm::BidirectionalBfs),
+        "dfs" => Ok(Algorithm::Dfs),
+        "dijkstra" => Ok(Algorithm::Dijkstra),
+        "astar" | "astar-manhattan" => Ok(Algorithm::AStar(Heuristic::Manhattan)),
+        "astar-euclidean" => Ok(Algorithm::AStar(Heuristic::Euclidean)),
+        "astar-chebyshev" => Ok(Algorithm::AStar(Heuristic::Chebyshev)),
+        "astar-zero" => Ok(Algorithm::AStar(Heuristic::Zero)),
+        "weighted-astar" => Ok(Algorithm::WeightedAStar(DEFAULT_ASTAR_WEIGHT)),
+        "greedy" => Ok(Algorithm::GreedyBestFirst),
+        "iddfs" => Ok(Algorithm::Iddfs),
+        "idastar" => Ok(Algorithm::IdaStar),
+        "jps" => Ok(Algorithm::JumpPointSearch),
+        "wall-follower-left" => Ok(Algorithm::WallFollower(Hand::Left)),
+        "wall-follower-right" => Ok(Algorithm::WallFollower(Hand::Right)),
+        "pledge" => Ok(Algorithm::Pledge),
+        "tremaux" => Ok(Algorithm::Tremaux),
+        "dead-end-filling" => Ok(Algorithm::DeadEndFilling),
+        "dfs-explorer" => Ok(Algorithm::DfsExplorer(0)),
+        "frontier-explorer" => Ok(Algorithm::FrontierExplorer(0)),
+        other => Err(format!("unknown algorithm `{}` (expected one of: {})", other, ALGORITHM_NAMES)),
+    }
+}
+
+fn parse_point(text: &str) -> Result<(usize, usize), String> {
+    let parsed = text.split_once(',').and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)));
+    parsed.ok_or_else(|| format!("expected `X,Y` cell coordinates, got `{}`", text))
+}
+
+impl Cli {
+    // Parses `std::env::args()` into the configs to run and where to export their results,
+    // exiting with a usage error on bad input. Without `--experiment` this is a single config
+    // built from the flags.
+    pub fn parse_configs() -> (Vec<Config>, ExportTargets) {
+        let mut cli = Cli::parse();
+        let experiment_path = cli.experiment.take();
+        let export_targets = ExportTargets { csv: cli.export_csv.take(), json: cli.export_json.take() };
+        let configs = cli.into_config().and_then(|config| match experiment_path {
+            Some(path) => experiment::load(&path, &config),
+            None => Ok(vec![config]),
+        });
+        match configs {
+            Ok(configs) => (configs, export_targets),
+            Err(message) => Cli::command().error(ErrorKind::ValueValidation, message).exit(),
+        }
+    }
+
+    fn into_config(self) -> Result<Config, String> {
+        let defaults = Config::default();
+        // A loaded maze fixes the dimensions, and its recorded seed names the output files.
+        let image_cell_size = self.image_cell_size.unwrap_or(defaults.image_cell_size);
+        let loaded = match self.load_maze {
+            Some(path) => Some(LoadedMaze::read(path, image_cell_size)?),
+            None => defaults.load_maze,
+        };
+        let config = Config {
+            screen_width: self.screen_width.unwrap_or(defaults.screen_width),
+            screen_height: self.screen_height.unwrap_or(defaults.screen_height),
+            use_perfect_maze: self.perfect || defaults.use_perfect_maze,
+            loop_percentage: self.loop_percentage.unwrap_or(defaults.loop_percentage),
+            generator: self.generator.unwrap_or(defaults.generator),
+            skip_visualization: self.skip_visualization || defaults.skip_visualization,
+            headless: self.headless || matches!(self.command, Some(Command::Headless)),
+            benchmark: match self.command {
+                Some(Command::Bench { mazes, repetitions }) => Some(BenchmarkPlan { mazes, repetitions }),
+                _ => defaults.benchmark,
+            },
+            dynamic: match self.command {
+                Some(Command::Dynamic { toggles, interval }) => Some(DynamicPlan { toggles, interval }),
+                _ => defaults.dynamic,
+            },
+            maze_width: loaded.as_ref().map_or(self.width.unwrap_or(defaults.maze_width), |loaded| loaded.maze.width),
+            maze_height: loaded.as_ref().map_or(self.height.unwrap_or(defaults.maze_height), |loaded| loaded.maze.height),
+            start_point: self.start.or(defaults.start_point),
+            end_point: self.end.or(defaults.end_point),
+            terrain: self.terrain || defaults.terrain,
+            batch_size: self.batch_size.unwrap_or(defaults.batch_size),
+            target_fps: self.fps.unwrap_or(defaults.target_fps),
+            seed: self.seed.or(loaded.as_ref().and_then(|loaded| loaded.seed)).or(defaults.seed),
+            png_dir: self.png_dir.or(defaults.png_dir),
+            png_cell_size: self.png_cell_size.unwrap_or(defaults.png_cell_size),
+            gif_dir: self.gif_dir.or(defaults.gif_dir),
+            gif_cell_size: self.gif_cell_size.unwrap_or(defaults.gif_cell_size),
+            svg_dir: self.svg_dir.or(defaults.svg_dir),
+            svg_layers: self.svg_layers.unwrap_or(defaults.svg_layers),
+            maze_dir: self.maze_dir.or(defaults.maze_dir),
+            maze_format: self.maze_format.unwrap_or(defaults.maze_format),
+            load_maze: loaded,
+            image_cell_size,
+            algorithms_to_run: self.algorithms.unwrap_or(defaults.algorithms_to_run),
+        };
+
+        if config.load_maze.is_none() && config.maze_width.saturating_mul(config.maze_height) > MAX_GENERATED_CELLS {
+            return Err(format!(
+                "a {}x{} maze is too large to generate, the limit is {} cells",
+                config.maze_width, config.maze_height, MAX_GENERATED_CELLS
+            ));
+        }
+        if config.algorithms_to_run.is_empty() {
+            return Err("`--algo` needs at least one algorithm".to_string());
+        }
+        for (flag, point) in [("--start", config.start_point), ("--end", config.end_point)] {
+            if let Some((x, y)) = point
+                && (x >= config.maze_width || y >= config.maze_height)
+            {
+                return Err(format!(
+                    "{} {},{} is outside the {}x{} maze",
+                    flag, x, y, config.maze_width, config.maze_height
+                ));
+            }
+        }
+        if !(0.0..=1.0).contains(&config.loop_percentage) {
+            return Err(format!("loop percentage {} must be between 0 and 1", config.loop_percentage));
+        }
+        // The window keeps a 50px margin on every side of the maze.
+        if !config.headless && config.benchmark.is_none() && config.dynamic.is_none() && (config.screen_width <= 100 || config.screen_height <= 100) {
+            return Err(format!(
+                "screen size {}x{} is too small, both sides must be larger than 100 pixels",
+                config.screen_width, config.screen_height
+            ));
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(args: &[&str]) -> Result<Config, String> {
+        let cli = Cli::try_parse_from(std::iter::once("knossos").chain(args.iter().copied())).map_err(|e| e.to_string())?;
+        cli.into_config()
+    }
+
+    #[test]
+    fn falls_back_to_defaults() {
+        let config = config(&[]).unwrap();
+        assert_eq!((config.maze_width, config.maze_height), (240, 140));
+        assert_eq!(config.algorithms_to_run, Config::default().algorithms_to_run);
+    }
+
+    #[test]
+    fn rejects_bad_sizes() {
+        for args in [
+            &["--width", "0"][..],
+            &["--height", "-3"],
+            &["--width", "abc"],
+            &["--width", "40000", "--height", "40000"],
+            &["--width", "5", "--start", "5,0"],
+            &["--end", "1,140"],
+            &["--loop-percentage", "1.5"],
+            &["--screen-width", "100"],
+        ] {
+            assert!(config(args).is_err(), "{:?} was accepted", args);
+        }
+    }
+
+    #[test]
+    fn parses_algorithm_names_and_parameters() {
+        let config = config(&["--algo", "bfs,weighted-astar=1.5,frontier-explorer=2,dfs-explorer"]).unwrap();
+        assert_eq!(
+            config.algorithms_to_run,
+            [Algorithm::Bfs, Algorithm::WeightedAStar(1.5), Algorithm::FrontierExplorer(2), Algorithm::DfsExplorer(0)]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_algorithms_and_bad_parameters() {
+        for name in ["nope", "astar-taxicab", "weighted-astar=0.5", "weighted-astar=inf", "dfs-explorer=x", "frontier-explorer=-1"] {
+            assert!(parse_algorithm(name).is_err(), "`{}` was accepted", name);
+        }
+        assert!(config(&["--algo", "bfs,nope"]).is_err());
+    }
+
+    #[test]
+    fn rejects_load_maze_with_maze_flags() {
+        for flag in [&["--generator", "prim"][..], &["--width", "10"], &["--perfect"], &["--loop-percentage", "0.2"]] {
+            let args: Vec<&str> = ["--load-maze", "maze.maze"].iter().chain(flag).copied().collect();
+            let message = config(&args).err().expect("the flags were accepted");
+            assert!(message.contains("cannot be used with"), "{:?}: {}", args, message);
+        }
+    }
+
+    #[test]
+    fn takes_size_and_seed_from_a_loaded_maze() {
+        let path = std::env::temp_dir().join(format!("knossos-cli-test-{}.maze", std::process::id()));
+        crate::text_format::save(&path, &crate::test_maze(9, 6, false), Some(11)).unwrap();
+        let config = config(&["--load-maze", path.to_str().unwrap()]);
+        std::fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+        assert_eq!((config.maze_width, config.maze_height, config.seed), (9, 6, Some(11)));
+        assert!(config.load_maze.is_some());
+    }
+}
@@ -0,0 +1,175 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{Heuristic, Maze};
+
+// Cost of a cell that cannot reach the goal.
+const INFINITY: usize = usize::MAX;
+
+// D* Lite priority: (min(g, rhs) + h + km, min(g, rhs)), compared lexicographically.
+type Key = (usize, usize);
+
+// D* Lite (Koenig and Likhachev): plans backwards from the end point, so the cost-to-goal of
+// every cell it has settled stays valid while the agent moves. When walls change, only the
+// cells next to the changed walls are re-evaluated, and the search repairs costs outward from
+// there until the agent's cell is consistent again, instead of starting over.
+//
+// `g` is a cell's settled cost to the goal and `rhs` the one-step lookahead from its neighbours;
+// a cell whose two values differ is queued. Entering a cell costs its terrain's cost.
+pub struct DStarLite {
+    start: (usize, usize),
+    // Where the agent was at the last replan, to correct the heuristic part of queued keys.
+    last: (usize, usize),
+    km: usize,
+    g: Vec<usize>,
+    rhs: Vec<usize>,
+    queue: BinaryHeap<Reverse<(Key, usize)>>,
+    // The key each cell is currently queued with; entries in `queue` that disagree are stale.
+    queued: Vec<Option<Key>>,
+}
+
+impl DStarLite {
+    pub fn new(maze: &Maze) -> Self {
+        let cells = maze.width * maze.height;
+        let mut planner = Self {
+            start: maze.start_point,
+            last: maze.start_point,
+            km: 0,
+            g: vec![INFINITY; cells],
+            rhs: vec![INFINITY; cells],
+            queue: BinaryHeap::new(),
+            queued: vec![None; cells],
+        };
+        let goal = maze.index(maze.end_point);
+        planner.rhs[goal] = 0;
+        planner.push(goal, (Heuristic::Manhattan.estimate(maze.start_point, maze.end_point) as usize, 0));
+        planner
+    }
+
+    fn key(&self, maze: &Maze, index: usize) -> Key {
+        let best = self.g[index].min(self.rhs[index]);
+        let estimate = Heuristic::Manhattan.estimate(self.start, maze.position(index)) as usize;
+        (best.saturating_add(estimate).saturating_add(self.km), best)
+    }
+
+    fn push(&mut self, index: usize, key: Key) {
+        self.queued[index] = Some(key);
+        self.queue.push(Reverse((key, index)));
+    }
+
+    // Cost of the agent's cell, i.e. of the best path from it to the end point.
+    pub fn path_cost(&self, maze: &Maze) -> Option<usize> {
+        Some(self.g[maze.index(self.start)]).filter(|&cost| cost != INFINITY)
+    }
+
+    // Recomputes `rhs` for one cell from its open neighbours and (re)queues it if inconsistent.
+    fn update_cell(&mut self, maze: &Maze, index: usize) {
+        let position = maze.position(index);
+        if position != maze.end_point {
+            self.rhs[index] = maze
+                .open_neighbors(position)
+                .map(|(_, next)| self.g[maze.index(next)].saturating_add(maze.cost(next)))
+                .min()
+                .unwrap_or(INFINITY);
+        }
+        if self.g[index] != self.rhs[index] {
+            let key = self.key(maze, index);
+            self.push(index, key);
+        } else {
+            self.queued[index] = None;
+        }
+    }
+
+    // Settles cells until the agent's cell is consistent and nothing queued could improve it.
+    // Returns the number of cells expanded.
+    pub fn compute(&mut self, maze: &Maze) -> usize {
+        let start = maze.index(self.start);
+        let mut expanded = 0;
+        while let Some(&Reverse((old_key, index))) = self.queue.peek() {
+            if self.queued[index] != Some(old_key) {
+                self.queue.pop();
+                continue;
+            }
+            if old_key >= self.key(maze, start) && self.rhs[start] == self.g[start] {
+                break;
+            }
+            self.queue.pop();
+            expanded += 1;
+
+            let new_key = self.key(maze, index);
+            if old_key < new_key {
+                self.push(index, new_key);
+                continue;
+            }
+            let position = maze.position(index);
+            if self.g[index] > self.rhs[index] {
+                self.g[index] = self.rhs[index];
+                self.queued[index] = None;
+            } else {
+                self.g[index] = INFINITY;
+                self.update_cell(maze, index);
+            }
+            for (_, next) in maze.open_neighbors(position) {
+                self.update_cell(maze, maze.index(next));
+            }
+        }
+        expanded
+    }
+
+    // Replans after the agent moved to `position` and the walls `(cell, dir)` in `changed`
+    // were opened or closed. Returns the number of cells expanded.
+    pub fn replan(&mut self, maze: &Maze, position: (usize, usize), changed: &[((usize, usize), usize)]) -> usize {
+        self.start = position;
+        self.km += Heuristic::Manhattan.estimate(self.last, position) as usize;
+        self.last = position;
+        for &(cell, dir) in changed {
+            self.update_cell(maze, maze.index(cell));
+            if let Some(next) = maze.neighbor(cell, dir) {
+                self.update_cell(maze, maze.index(next));
+            }
+        }
+        self.compute(maze)
+    }
+
+    // The neighbour of `position` to step to next, or `None` if the end point is unreachable.
+    pub fn next_step(&self, maze: &Maze, position: (usize, usize)) -> Option<(usize, usize)> {
+        maze.open_neighbors(position)
+            .map(|(_, next)| (self.g[maze.index(next)].saturating_add(maze.cost(next)), next))
+            .filter(|&(cost, _)| cost != INFINITY)
+            .min_by_key(|&(cost, _)| cost)
+            .map(|(_, next)| next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamic::toggle_walls;
+    use crate::pathfinding::{Dijkstra, Pathfinder};
+    use crate::test_maze;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn replanning_matches_a_fresh_search() {
+        for terrain in [false, true] {
+            let mut maze = test_maze(16, 12, terrain);
+            let mut rng = ChaCha8Rng::seed_from_u64(3);
+            let mut planner = DStarLite::new(&maze);
+            planner.compute(&maze);
+            let mut position = maze.start_point;
+            for _ in 0..40 {
+                if let Some(next) = planner.next_step(&maze, position) {
+                    position = next;
+                }
+                let changed = toggle_walls(&mut maze, 4, &mut rng);
+                planner.replan(&maze, position, &changed);
+
+                maze.start_point = position;
+                let outcome = Dijkstra.search(&maze);
+                let expected = outcome.found.then(|| maze.path_cost(&outcome.path));
+                assert_eq!(planner.path_cost(&maze), expected, "agent at {:?}", position);
+            }
+        }
+    }
+}
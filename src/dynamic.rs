@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::dstar_lite::DStarLite;
+use crate::pathfinding::{Bfs, Dijkstra, Pathfinder};
+use crate::{build_maze, Config, DynamicPlan, Maze};
+
+// The agent gives up after this many steps per cell, e.g. when changes keep the end cut off.
+const STEPS_PER_CELL: usize = 4;
+
+// Total work of one replanning strategy over a run.
+#[derive(Default)]
+struct Cost {
+    expanded: usize,
+    time: Duration,
+}
+
+// Walks an agent from the start to the end point while `plan.toggles` random interior walls
+// open or close every `plan.interval` steps. After every change D* Lite repairs its plan, and
+// for comparison a full search from the agent's cell (BFS, or Dijkstra on terrain) is run on
+// the same maze. The agent follows the D* Lite plan and waits in place while no path exists.
+pub fn run(config: &Config, plan: DynamicPlan) {
+    let seed = config.resolve_seed();
+    let mut maze = build_maze(config, seed);
+    // Same seed as the maze, but its own stream, so changing the plan never changes the maze.
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(1);
+    let full_search: Box<dyn Pathfinder> = if maze.is_weighted() { Box::new(Dijkstra) } else { Box::new(Bfs) };
+    let full_name = if maze.is_weighted() { "Full Dijkstra" } else { "Full BFS" };
+
+    let mut planner = DStarLite::new(&maze);
+    let initial_time = Instant::now();
+    let initial_expanded = planner.compute(&maze);
+    let initial_time = initial_time.elapsed();
+    let initial_full = full_search.search(&maze);
+
+    let mut replanning = Cost::default();
+    let mut recomputing = Cost::default();
+    let (mut changes, mut steps, mut moves) = (0, 0, 0);
+    // Changes after which D* Lite's path cost differed from the full search's, which would be a bug.
+    let mut disagreements = 0;
+    let step_limit = STEPS_PER_CELL * maze.width * maze.height;
+    let mut position = maze.start_point;
+
+    while position != maze.end_point && steps < step_limit {
+        if steps > 0 && steps % plan.interval == 0 {
+            let changed = toggle_walls(&mut maze, plan.toggles, &mut rng);
+            changes += 1;
+
+            let start_time = Instant::now();
+            replanning.expanded += planner.replan(&maze, position, &changed);
+            replanning.time += start_time.elapsed();
+
+            maze.start_point = position;
+            let outcome = full_search.search(&maze);
+            recomputing.expanded += outcome.expanded;
+            recomputing.time += outcome.elapsed;
+            let full_cost = outcome.found.then(|| maze.path_cost(&outcome.path));
+            if planner.path_cost(&maze) != full_cost {
+                disagreements += 1;
+            }
+        }
+        if let Some(next) = planner.next_step(&maze, position) {
+            position = next;
+            moves += 1;
+        }
+        steps += 1;
+    }
+
+    println!("Dynamic Maze:    {} walls toggled every {} steps", plan.toggles, plan.interval);
+    println!("Maze Type:       {}", config.maze_type_label());
+    if let Some(generator) = config.generator_label() {
+        println!("Generator:       {}", generator);
+    }
+    println!("Seed:            {}", seed);
+    println!("Maze Dimensions: {}x{}", config.maze_width, config.maze_height);
+    if position == maze.end_point {
+        println!("Agent:           reached the end in {} steps ({} spent waiting)", steps, steps - moves);
+    } else {
+        println!("Agent:           gave up after {} steps ({} spent waiting)", steps, steps - moves);
+    }
+    println!("Changes:         {}", changes);
+    println!();
+    println!("{:<16} {:>14} {:>14} {:>12} {:>16}", "Planner", "Initial Steps", "Replan Steps", "Per Change", "Per Change (us)");
+    println!("{}", "-".repeat(76));
+    for (name, initial, cost) in [
+        ("D* Lite", initial_expanded, &replanning),
+        (full_name, initial_full.expanded, &recomputing),
+    ] {
+        let per_change = |value: f64| if changes == 0 { 0.0 } else { value / changes as f64 };
+        println!(
+            "{:<16} {:>14} {:>14} {:>12.1} {:>16.3}",
+            name,
+            initial,
+            cost.expanded,
+            per_change(cost.expanded as f64),
+            per_change(cost.time.as_nanos() as f64 / 1000.0),
+        );
+    }
+    println!();
+    println!(
+        "Initial plan:    {:.3} ms with D* Lite, {:.3} ms with a full search",
+        initial_time.as_secs_f64() * 1000.0,
+        initial_full.elapsed.as_secs_f64() * 1000.0
+    );
+    println!("Path costs:      D* Lite and the full search disagreed after {} of {} changes", disagreements, changes);
+}
+
+// Opens or closes `count` random walls between two cells and returns them as `(cell, dir)`.
+pub fn toggle_walls(maze: &mut Maze, count: usize, rng: &mut ChaCha8Rng) -> Vec<((usize, usize), usize)> {
+    let mut changed = Vec::with_capacity(count);
+    // A single row or column has no interior wall in one of the two directions.
+    let dirs: Vec<usize> = [1, 2].into_iter().filter(|&dir| maze.neighbor((0, 0), dir).is_some()).collect();
+    if dirs.is_empty() {
+        return changed;
+    }
+    for _ in 0..count {
+        let dir = dirs[rng.random_range(0..dirs.len())];
+        let (max_x, max_y) = if dir == 1 { (maze.width - 1, maze.height) } else { (maze.width, maze.height - 1) };
+        let cell = (rng.random_range(0..max_x), rng.random_range(0..max_y));
+        let wall = maze.has_wall(cell, dir);
+        maze.set_wall(cell, dir, !wall);
+        changed.push((cell, dir));
+    }
+    changed
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::cli::parse_algorithm;
+use crate::{Config, Generator, MAX_GENERATED_CELLS};
+
+// An experiment file: every maze in `mazes` is run with every algorithm in `algorithms`.
+//
+// ```toml
+// algorithms = ["bfs", "dfs", "astar-euclidean"]
+//
+// [[mazes]]
+// width = 240
+// height = 140
+// generator = "wilson"
+// loop_percentage = 0.08
+// terrain = true
+// seed = 42
+// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExperimentFile {
+    algorithms: Vec<String>,
+    mazes: Vec<MazeSpec>,
+}
+
+// Anything left out keeps the value from the command line (or `Config::default()`).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MazeSpec {
+    width: Option<usize>,
+    height: Option<usize>,
+    generator: Option<Generator>,
+    // 0 means a perfect maze.
+    loop_percentage: Option<f32>,
+    terrain: Option<bool>,
+    seed: Option<u64>,
+}
+
+// Reads a `.toml` or `.json` experiment file and expands it into one `Config` per maze,
+// each inheriting the window and animation settings from `base`.
+pub fn load(path: &Path, base: &Config) -> Result<Vec<Config>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let file: ExperimentFile = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?,
+        Some("json") => serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?,
+        _ => return Err(format!("{}: experiment files must end in .toml or .json", path.display())),
+    };
+
+    let algorithms = file
+        .algorithms
+        .iter()
+        .map(|name| parse_algorithm(name))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    if algorithms.is_empty() {
+        return Err(format!("{}: `algorithms` needs at least one algorithm", path.display()));
+    }
+    if file.mazes.is_empty() {
+        return Err(format!("{}: `mazes` needs at least one maze", path.display()));
+    }
+
+    file.mazes
+        .into_iter()
+        .enumerate()
+        .map(|(i, spec)| {
+            let loop_percentage = spec.loop_percentage.unwrap_or(if base.use_perfect_maze { 0.0 } else { base.loop_percentage });
+            let config = Config {
+                maze_width: spec.width.unwrap_or(base.maze_width),
+                maze_height: spec.height.unwrap_or(base.maze_height),
+                generator: spec.generator.unwrap_or(base.generator),
+                use_perfect_maze: loop_percentage == 0.0,
+                loop_percentage,
+                terrain: spec.terrain.unwrap_or(base.terrain),
+                seed: spec.seed.or(base.seed),
+                algorithms_to_run: algorithms.clone(),
+                ..base.clone()
+            };
+
+            if config.maze_width == 0 || config.maze_height == 0 {
+                return Err(format!("{}: maze {} must be at least 1x1", path.display(), i + 1));
+            }
+            if config.maze_width.saturating_mul(config.maze_height) > MAX_GENERATED_CELLS {
+                return Err(format!("{}: maze {} has more than {} cells", path.display(), i + 1, MAX_GENERATED_CELLS));
+            }
+            if !(0.0..=1.0).contains(&config.loop_percentage) {
+                return Err(format!("{}: maze {} loop_percentage must be between 0 and 1", path.display(), i + 1));
+            }
+            if [config.start_point, config.end_point]
+                .into_iter()
+                .flatten()
+                .any(|(x, y)| x >= config.maze_width || y >= config.maze_height)
+            {
+                return Err(format!("{}: maze {} is too small for `--start`/`--end`", path.display(), i + 1));
+            }
+            Ok(config)
+        })
+        .collect()
+}
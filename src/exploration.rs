@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::bitset::BitSet;
+use crate::pathfinding::{Pathfinder, SearchOutcome, Walk};
+use crate::{Heuristic, Maze};
+
+// Agents that explore under fog of war. The searches in `pathfinding` read the whole maze and
+// jump straight to whichever cell they expand next; an explorer only knows the walls of cells
+// it has sensed (those within `sensor_radius` cells, as a square, of somewhere it has stood)
+// and has to walk everywhere, so backtracking costs steps like any other move. The reported
+// path is the whole walk, and its length the physical distance travelled. As in micromouse,
+// the agent knows the coordinates of the end point but not the way there.
+
+// What an explorer knows about the maze: the walls of every cell it has sensed.
+pub struct FogOfWar<'a> {
+    maze: &'a Maze,
+    sensor_radius: usize,
+    sensed: BitSet,
+}
+
+impl<'a> FogOfWar<'a> {
+    pub fn new(maze: &'a Maze, sensor_radius: usize) -> Self {
+        Self { maze, sensor_radius, sensed: BitSet::new(maze.width * maze.height, false) }
+    }
+
+    // Reveals the walls of every cell within the sensor radius of `position`.
+    pub fn sense(&mut self, (x, y): (usize, usize)) {
+        let radius = self.sensor_radius;
+        for sy in y.saturating_sub(radius)..=(y + radius).min(self.maze.height - 1) {
+            for sx in x.saturating_sub(radius)..=(x + radius).min(self.maze.width - 1) {
+                self.sensed.set(self.maze.index((sx, sy)), true);
+            }
+        }
+    }
+
+    pub fn is_sensed(&self, position: (usize, usize)) -> bool {
+        self.sensed.get(self.maze.index(position))
+    }
+
+    // The directions out of `position` known to be open, with the cell each leads to. Nothing
+    // is known about cells that have not been sensed.
+    pub fn open_neighbors(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, (usize, usize))> + '_ {
+        let known = self.is_sensed(position);
+        self.maze.open_neighbors(position).filter(move |_| known)
+    }
+}
+
+// Whether `position` is known to be a dead end that is not the end point, so not worth entering:
+// its only opening is the way in. With a sensor radius of 0 this is never known in advance.
+fn is_dead_end(fog: &FogOfWar, maze: &Maze, position: (usize, usize)) -> bool {
+    position != maze.end_point && fog.is_sensed(position) && fog.open_neighbors(position).count() == 1
+}
+
+// Depth-first exploration: step into an unvisited neighbour, the one closest to the end point
+// first, skipping sensed dead ends, and walk back the way it came when there is none. Memory is the route back to the
+// start, reported as the frontier peak.
+pub struct DfsExplorer {
+    pub sensor_radius: usize,
+}
+
+impl Pathfinder for DfsExplorer {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut fog = FogOfWar::new(maze, self.sensor_radius);
+        let mut walk = Walk::new(maze);
+        // Direction of every step on the route from the start, to retrace when backtracking.
+        let mut route: Vec<usize> = Vec::new();
+        let mut route_peak = 0;
+
+        while walk.position != maze.end_point {
+            fog.sense(walk.position);
+            let next = fog
+                .open_neighbors(walk.position)
+                .filter(|&(_, next)| !walk.has_entered(maze, next) && !is_dead_end(&fog, maze, next))
+                .min_by_key(|&(_, next)| Heuristic::Manhattan.estimate(next, maze.end_point) as usize);
+            match next {
+                Some((dir, _)) => {
+                    walk.step(maze, dir);
+                    route.push(dir);
+                    route_peak = route_peak.max(route.len());
+                }
+                None => match route.pop() {
+                    Some(dir) => walk.step(maze, (dir + 2) % 4),
+                    // Back at the start with nothing left to try: the end is unreachable.
+                    None => break,
+                },
+            }
+        }
+
+        let mut outcome = walk.finish(maze, false, start_time);
+        outcome.frontier_peak = route_peak;
+        outcome
+    }
+}
+
+// Frontier-based exploration: from the map sensed so far, pick the most promising frontier cell
+// (an unsensed cell behind a known opening), scoring each by the walk to it plus its distance
+// to the end point, walk there along the shortest known route, and plan again. Once the end
+// point itself shows up on the map it is walked to directly.
+pub struct FrontierExplorer {
+    pub sensor_radius: usize,
+}
+
+// Scratch space for `FrontierExplorer` route planning, reused between plans. A cell belongs to
+// the current plan's search when its stamp equals `generation`, which saves clearing the
+// arrays every time.
+struct RoutePlanner {
+    stamp: Vec<u32>,
+    generation: u32,
+    // Direction taken into each cell by the planning BFS.
+    came_by: Vec<u8>,
+    queue: VecDeque<((usize, usize), usize)>,
+    queue_peak: usize,
+}
+
+impl RoutePlanner {
+    fn new(maze: &Maze) -> Self {
+        let cells = maze.width * maze.height;
+        Self { stamp: vec![0; cells], generation: 0, came_by: vec![0; cells], queue: VecDeque::new(), queue_peak: 0 }
+    }
+
+    // Directions from `position` to the best target on the known map, or `None` if there is no
+    // frontier left and the end point is not on the map.
+    fn plan(&mut self, maze: &Maze, fog: &FogOfWar, position: (usize, usize)) -> Option<Vec<usize>> {
+        self.generation += 1;
+        self.queue.clear();
+        self.queue.push_back((position, 0));
+        self.stamp[maze.index(position)] = self.generation;
+
+        // Best target so far as (score, cell).
+        let mut best: Option<(usize, (usize, usize))> = None;
+        while let Some((current, steps)) = self.queue.pop_front() {
+            // Every cell still queued is at least this far away, so none can beat `best`.
+            if best.is_some_and(|(score, _)| steps >= score) {
+                break;
+            }
+            if current != position && (current == maze.end_point || !fog.is_sensed(current)) {
+                let score = steps + Heuristic::Manhattan.estimate(current, maze.end_point) as usize;
+                if best.is_none_or(|(best_score, _)| score < best_score) {
+                    best = Some((score, current));
+                }
+                continue;
+            }
+            for (dir, next) in fog.open_neighbors(current) {
+                if self.stamp[maze.index(next)] != self.generation {
+                    self.stamp[maze.index(next)] = self.generation;
+                    self.came_by[maze.index(next)] = dir as u8;
+                    self.queue.push_back((next, steps + 1));
+                }
+            }
+            self.queue_peak = self.queue_peak.max(self.queue.len());
+        }
+
+        let (_, mut cell) = best?;
+        let mut route = Vec::new();
+        while cell != position {
+            let dir = self.came_by[maze.index(cell)] as usize;
+            route.push(dir);
+            cell = maze.neighbor(cell, (dir + 2) % 4).expect("the route came from this side");
+        }
+        route.reverse();
+        Some(route)
+    }
+}
+
+impl Pathfinder for FrontierExplorer {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut fog = FogOfWar::new(maze, self.sensor_radius);
+        let mut walk = Walk::new(maze);
+        let mut planner = RoutePlanner::new(maze);
+        fog.sense(walk.position);
+
+        while walk.position != maze.end_point {
+            let Some(route) = planner.plan(maze, &fog, walk.position) else { break };
+            for dir in route {
+                walk.step(maze, dir);
+                fog.sense(walk.position);
+            }
+        }
+
+        let mut outcome = walk.finish(maze, false, start_time);
+        outcome.frontier_peak = planner.queue_peak;
+        outcome
+    }
+}
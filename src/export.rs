@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::{Config, Generator, PathfindingResult};
+
+// One row per algorithm run, flat so it loads straight into a dataframe.
+#[derive(Serialize)]
+pub struct RunRecord {
+    maze_width: usize,
+    maze_height: usize,
+    // Generator and loop percentage are empty for mazes loaded from a file, which fill in
+    // `loaded_from` instead.
+    generator: Option<Generator>,
+    // 0 for perfect mazes.
+    loop_percentage: Option<f32>,
+    loaded_from: Option<String>,
+    // Empty for a loaded maze whose file recorded no seed.
+    seed: Option<u64>,
+    algorithm: String,
+    // Index of the timed run on this maze; always 0 outside benchmark mode.
+    repetition: usize,
+    found: bool,
+    looped: bool,
+    gave_up: bool,
+    steps: usize,
+    reexpanded: usize,
+    frontier_peak: usize,
+    duration_ns: u64,
+    path_len: usize,
+    path_cost: usize,
+    // BFS path length on the same maze, empty when BFS was not run or the maze has terrain.
+    optimal_path_len: Option<usize>,
+    // Dijkstra path cost on a maze with terrain, empty when Dijkstra was not run or there is no terrain.
+    optimal_path_cost: Option<usize>,
+    visited: usize,
+}
+
+impl RunRecord {
+    pub fn new(config: &Config, seed: u64, result: &PathfindingResult, repetition: usize) -> Self {
+        Self {
+            maze_width: config.maze_width,
+            maze_height: config.maze_height,
+            generator: config.load_maze.is_none().then_some(config.generator),
+            loop_percentage: config
+                .load_maze
+                .is_none()
+                .then_some(if config.use_perfect_maze { 0.0 } else { config.loop_percentage }),
+            loaded_from: config.load_maze.as_ref().map(|loaded| loaded.path.display().to_string()),
+            seed: config.maze_seed(seed),
+            algorithm: result.name.clone(),
+            repetition,
+            found: result.found,
+            looped: result.looped,
+            gave_up: result.gave_up,
+            steps: result.steps,
+            reexpanded: result.reexpanded,
+            frontier_peak: result.frontier_peak,
+            duration_ns: result.duration.as_nanos() as u64,
+            path_len: result.path_len,
+            path_cost: result.path_cost,
+            optimal_path_len: result.reference.filter(|reference| !reference.weighted).map(|reference| reference.value),
+            optimal_path_cost: result.reference.filter(|reference| reference.weighted).map(|reference| reference.value),
+            visited: result.visited,
+        }
+    }
+}
+
+// Files the collected `RunRecord`s are written to once every run has finished.
+#[derive(Default)]
+pub struct ExportTargets {
+    pub csv: Option<PathBuf>,
+    pub json: Option<PathBuf>,
+}
+
+impl ExportTargets {
+    pub fn write(&self, records: &[RunRecord]) -> Result<(), String> {
+        if let Some(path) = &self.csv {
+            let mut writer = csv::Writer::from_path(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            for record in records {
+                writer.serialize(record).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+            writer.flush().map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        if let Some(path) = &self.json {
+            let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            serde_json::to_writer_pretty(BufWriter::new(file), records)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use std::cmp::Ordering;
+
+use rand::prelude::IteratorRandom;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+use crate::bitset::BitSet;
+use crate::Maze;
+
+// A strategy for carving a perfect maze (a spanning tree over the cells) out of a grid
+// whose walls are all closed. Each algorithm leaves a different texture: long winding
+// corridors for the backtracker, short branchy dead ends for Prim, and so on.
+// All randomness must come from `rng` so a seed reproduces the same maze.
+pub trait MazeGenerator {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore);
+}
+
+// One direction (0 = up, 1 = right, 2 = down, 3 = left) per cell, packed two bits per cell.
+struct Directions(BitSet);
+
+impl Directions {
+    fn new(cells: usize) -> Self {
+        Self(BitSet::new(2 * cells, false))
+    }
+
+    fn get(&self, index: usize) -> usize {
+        usize::from(self.0.get(2 * index)) | usize::from(self.0.get(2 * index + 1)) << 1
+    }
+
+    fn set(&mut self, index: usize, dir: usize) {
+        self.0.set(2 * index, dir & 1 == 1);
+        self.0.set(2 * index + 1, dir & 2 == 2);
+    }
+}
+
+// Depth-first "recursive" backtracker. Instead of a stack of cells, every cell remembers the
+// direction back to the cell it was carved from, so backtracking costs two bits per cell.
+// Produces long corridors with few, deep dead ends.
+pub struct RecursiveBacktracker;
+
+impl MazeGenerator for RecursiveBacktracker {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut back = Directions::new(maze.width * maze.height);
+        let mut current = (0, 0);
+        maze.mark_visited(current);
+        loop {
+            let next = maze
+                .neighbors(current)
+                .filter(|&(_, next)| !maze.is_visited(next))
+                .choose(rng);
+            if let Some((dir, next)) = next {
+                maze.carve_passage(current, dir);
+                maze.mark_visited(next);
+                back.set(maze.index(next), (dir + 2) % 4);
+                current = next;
+            } else if current == (0, 0) {
+                break;
+            } else {
+                current = maze.neighbor(current, back.get(maze.index(current))).expect("carved from this side");
+            }
+        }
+    }
+}
+
+// Randomized Prim's algorithm: grows the maze from a single cell by opening a random
+// wall on its boundary. Produces many short dead ends radiating from the start.
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        // Boundary walls, stored as `4 * index + dir` of the cell inside the maze.
+        let mut frontier: Vec<u32> = Vec::new();
+        let wall = |maze: &Maze, cell, dir: usize| (4 * maze.index(cell) + dir) as u32;
+
+        let start = (rng.random_range(0..maze.width), rng.random_range(0..maze.height));
+        maze.mark_visited(start);
+        frontier.extend(maze.neighbors(start).map(|(dir, _)| wall(maze, start, dir)));
+
+        while !frontier.is_empty() {
+            let entry = frontier.swap_remove(rng.random_range(0..frontier.len())) as usize;
+            let (cell, dir) = (maze.position(entry / 4), entry % 4);
+            let Some(next) = maze.neighbor(cell, dir) else { continue };
+            if maze.is_visited(next) {
+                continue;
+            }
+            maze.carve_passage(cell, dir);
+            maze.mark_visited(next);
+            frontier.extend(
+                maze.neighbors(next)
+                    .filter(|&(_, other)| !maze.is_visited(other))
+                    .map(|(dir, _)| wall(maze, next, dir)),
+            );
+        }
+    }
+}
+
+// Disjoint-set forest over cell indices, used by Kruskal's algorithm.
+struct UnionFind {
+    parent: Vec<u32>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self { parent: (0..size as u32).collect(), rank: vec![0; size] }
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let mut node = node as u32;
+        while self.parent[node as usize] != node {
+            // Path halving keeps the trees shallow without recursion.
+            self.parent[node as usize] = self.parent[self.parent[node as usize] as usize];
+            node = self.parent[node as usize];
+        }
+        node as usize
+    }
+
+    // Merges the sets containing `a` and `b`. Returns false if they were already joined.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+        match self.rank[root_a].cmp(&self.rank[root_b]) {
+            Ordering::Less => self.parent[root_a] = root_b as u32,
+            Ordering::Greater => self.parent[root_b] = root_a as u32,
+            Ordering::Equal => {
+                self.parent[root_b] = root_a as u32;
+                self.rank[root_a] += 1;
+            }
+        }
+        true
+    }
+}
+
+// Randomized Kruskal's algorithm: visits every interior wall in random order and removes
+// it when the cells on either side are not yet connected.
+pub struct Kruskal;
+
+impl MazeGenerator for Kruskal {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        // Only east (1) and south (2) walls, so each interior wall is listed once, numbered
+        // like its bit in `Maze::walls`: `2 * index` east, `2 * index + 1` south.
+        let interior = (maze.width - 1) * maze.height + maze.width * (maze.height - 1);
+        let mut walls: Vec<u32> = Vec::with_capacity(interior);
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                let bit = 2 * maze.index((x, y)) as u32;
+                if x + 1 < maze.width {
+                    walls.push(bit);
+                }
+                if y + 1 < maze.height {
+                    walls.push(bit + 1);
+                }
+            }
+        }
+        walls.shuffle(rng);
+
+        let mut sets = UnionFind::new(maze.width * maze.height);
+        for bit in walls {
+            let (cell, dir) = (maze.position(bit as usize / 2), 1 + bit as usize % 2);
+            let Some(next) = maze.neighbor(cell, dir) else { continue };
+            if sets.union(maze.index(cell), maze.index(next)) {
+                maze.carve_passage(cell, dir);
+            }
+        }
+    }
+}
+
+// Wilson's algorithm: loop-erased random walks from each unvisited cell until they hit the
+// maze. Samples uniformly from all spanning trees, so it has no directional bias.
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        // Direction last taken out of each cell during the current walk. Overwriting it on
+        // revisits is what erases the loops.
+        let mut exit_dir = Directions::new(maze.width * maze.height);
+
+        let root = (rng.random_range(0..maze.width), rng.random_range(0..maze.height));
+        maze.mark_visited(root);
+
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                if maze.is_visited((x, y)) {
+                    continue;
+                }
+
+                let mut current = (x, y);
+                while !maze.is_visited(current) {
+                    let (dir, next) = maze.neighbors(current).choose(rng).unwrap();
+                    exit_dir.set(maze.index(current), dir);
+                    current = next;
+                }
+
+                let mut current = (x, y);
+                while !maze.is_visited(current) {
+                    let dir = exit_dir.get(maze.index(current));
+                    maze.mark_visited(current);
+                    maze.carve_passage(current, dir);
+                    current = maze.neighbor(current, dir).unwrap();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::export::RunRecord;
+use crate::{build_maze, export_gif, export_png, export_svg, get_algorithm_info, reference_path, Config, PathfindingResult, Reference};
+
+// Runs the configured algorithms without touching minifb, so it works on machines with
+// no display (CI boxes, servers). Results are printed to stdout as a table and returned
+// as records for export.
+pub fn run(config: &Config) -> Vec<RunRecord> {
+    let seed = config.resolve_seed();
+    let maze = build_maze(config, seed);
+    export_svg(config, seed, &maze, None);
+
+    println!("Maze Type:       {}", config.maze_type_label());
+    if let Some(generator) = config.generator_label() {
+        println!("Generator:       {}", generator);
+    }
+    println!("Seed:            {}", config.seed_label(seed));
+    println!("Maze Dimensions: {}x{}", config.maze_width, config.maze_height);
+    println!();
+
+    let reference = reference_path(config, &maze);
+    let results: Vec<PathfindingResult> = config
+        .algorithms_to_run
+        .iter()
+        .map(|&algo| {
+            let info = get_algorithm_info(algo);
+            let outcome = info.pathfinder.search(&maze);
+            export_png(config, seed, &maze, &info, &outcome);
+            export_gif(config, seed, &maze, &info, &outcome);
+            export_svg(config, seed, &maze, Some((&info, &outcome)));
+            PathfindingResult::new(&info, &outcome, &maze, reference)
+        })
+        .collect();
+
+    print_results_table(&results, maze.is_weighted(), reference);
+    results.iter().map(|result| RunRecord::new(config, seed, result, 0)).collect()
+}
+
+// The Path Cost column only appears on mazes with terrain, where it can differ from the length,
+// and the vs BFS (vs Dijkstra, on terrain) column only when that algorithm ran and found a path.
+fn print_results_table(results: &[PathfindingResult], weighted: bool, reference: Option<Reference>) {
+    let compared = reference.is_some();
+    let cost_header = if weighted { format!(" {:>10}", "Path Cost") } else { String::new() };
+    let excess_header = reference.map(|reference| format!(" {:>11}", format!("vs {}", reference.name()))).unwrap_or_default();
+    println!(
+        "{:<24} {:>10} {:>12} {:>14} {:>12} {:>10}{}{}",
+        "Algorithm", "Steps", "Re-expanded", "Frontier Peak", "Time (ms)", "Path Len", cost_header, excess_header
+    );
+    println!("{}", "-".repeat(87 + if weighted { 11 } else { 0 } + if compared { 12 } else { 0 }));
+    for result in results {
+        let (path_len, path_cost) = if result.found {
+            (result.path_len.to_string(), result.path_cost.to_string())
+        } else if result.looped {
+            ("looped".to_string(), "looped".to_string())
+        } else if result.gave_up {
+            ("gave up".to_string(), "gave up".to_string())
+        } else {
+            ("none".to_string(), "none".to_string())
+        };
+        let cost_column = if weighted { format!(" {:>10}", path_cost) } else { String::new() };
+        let excess_column = match result.excess() {
+            Some(excess) if compared => format!(" {:>10.1}%", excess),
+            _ if compared => format!(" {:>11}", "-"),
+            _ => String::new(),
+        };
+        println!(
+            "{:<24} {:>10} {:>12} {:>14} {:>12.3} {:>10}{}{}",
+            result.name,
+            result.steps,
+            result.reexpanded,
+            result.frontier_peak,
+            result.duration.as_secs_f64() * 1000.0,
+            path_len,
+            cost_column,
+            excess_column,
+        );
+    }
+}
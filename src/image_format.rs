@@ -0,0 +1,302 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::Maze;
+
+// Turns a black-and-white bitmap into a maze, so searches can run on hand-drawn or third-party
+// mazes. Every `cell_size` x `cell_size` block of pixels becomes one cell: dark blocks are solid
+// wall, light ones are passages, and passages are connected to their light neighbours. A green
+// pixel marks the start and a red one the end; without markers the first and last passage
+// cells (in reading order) are used. PNG and PBM (P1 and P4) are supported.
+
+// What a single pixel stands for.
+#[derive(Clone, Copy, PartialEq)]
+enum Pixel {
+    Wall,
+    Open,
+    Start,
+    End,
+}
+
+// Classifies an RGB pixel. Markers are checked first because pure red is darker than the
+// wall threshold.
+fn classify(r: u8, g: u8, b: u8) -> Pixel {
+    let (r, g, b) = (r as i32, g as i32, b as i32);
+    if g >= 128 && g - r >= 64 && g - b >= 64 {
+        Pixel::Start
+    } else if r >= 128 && r - g >= 64 && r - b >= 64 {
+        Pixel::End
+    } else if r * 299 + g * 587 + b * 114 < 128_000 {
+        Pixel::Wall
+    } else {
+        Pixel::Open
+    }
+}
+
+// A decoded image as one `Pixel` per pixel, row-major.
+struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+}
+
+fn decode_png(bytes: &[u8]) -> Result<Bitmap, String> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size().ok_or("image is too large")?];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+
+    let channels = info.color_type.samples();
+    let pixels = buffer[..info.buffer_size()]
+        .chunks_exact(info.line_size)
+        .flat_map(|row| row[..info.width as usize * channels].chunks_exact(channels))
+        .map(|pixel| {
+            let (rgb, alpha) = match pixel {
+                [v] => ([*v; 3], 255),
+                [v, a] => ([*v; 3], *a),
+                [r, g, b] => ([*r, *g, *b], 255),
+                [r, g, b, a] => ([*r, *g, *b], *a),
+                _ => unreachable!("PNG pixels have one to four channels"),
+            };
+            // Transparent areas count as background, so blend them over white.
+            let over_white = |v: u8| ((v as u32 * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8;
+            classify(over_white(rgb[0]), over_white(rgb[1]), over_white(rgb[2]))
+        })
+        .collect();
+    Ok(Bitmap { width: info.width as usize, height: info.height as usize, pixels })
+}
+
+// Next whitespace-separated PBM header token, skipping `#` comments that run to the end of the line.
+fn next_token<'a>(bytes: &'a [u8], position: &mut usize) -> Result<&'a [u8], String> {
+    loop {
+        match bytes.get(*position) {
+            Some(b'#') => {
+                while bytes.get(*position).is_some_and(|&b| b != b'\n') {
+                    *position += 1;
+                }
+            }
+            Some(b) if b.is_ascii_whitespace() => *position += 1,
+            Some(_) => break,
+            None => return Err("unexpected end of PBM header".to_string()),
+        }
+    }
+    let start = *position;
+    while bytes.get(*position).is_some_and(|b| !b.is_ascii_whitespace()) {
+        *position += 1;
+    }
+    Ok(&bytes[start..*position])
+}
+
+// Netpbm bitmaps, ASCII (`P1`) or packed binary (`P4`). A set bit is black, i.e. wall.
+fn decode_pbm(bytes: &[u8]) -> Result<Bitmap, String> {
+    let mut position = 0;
+    let magic = next_token(bytes, &mut position)?;
+    let mut size = [0usize; 2];
+    for (value, name) in size.iter_mut().zip(["width", "height"]) {
+        let token = next_token(bytes, &mut position)?;
+        *value = std::str::from_utf8(token)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| format!("invalid PBM {}", name))?;
+    }
+    let [width, height] = size;
+    let pixel_count = width.checked_mul(height).ok_or("PBM size is too large")?;
+    // Exactly one whitespace byte separates the header from packed data.
+    position += 1;
+
+    let pixels = match magic {
+        b"P1" => {
+            let bits: Vec<Pixel> = bytes[position.min(bytes.len())..]
+                .iter()
+                .filter_map(|b| match b {
+                    b'1' => Some(Pixel::Wall),
+                    b'0' => Some(Pixel::Open),
+                    _ => None,
+                })
+                .take(pixel_count)
+                .collect();
+            if bits.len() < pixel_count {
+                return Err("PBM pixel data is truncated".to_string());
+            }
+            bits
+        }
+        b"P4" => {
+            let row_bytes = width.div_ceil(8);
+            let end = row_bytes.checked_mul(height).and_then(|len| len.checked_add(position)).ok_or("PBM size is too large")?;
+            let data = bytes.get(position..end).ok_or("PBM pixel data is truncated")?;
+            data.chunks_exact(row_bytes)
+                .flat_map(|row| {
+                    (0..width).map(move |x| if (row[x / 8] >> (7 - x % 8)) & 1 == 1 { Pixel::Wall } else { Pixel::Open })
+                })
+                .collect()
+        }
+        _ => return Err("only P1 and P4 PBM bitmaps are supported".to_string()),
+    };
+    Ok(Bitmap { width, height, pixels })
+}
+
+// Reduces each `cell_size` block to one pixel. A block containing a marker becomes that
+// marker, otherwise whichever of wall or passage covers more of it wins.
+fn downsample(bitmap: &Bitmap, cell_size: usize) -> Bitmap {
+    let width = bitmap.width / cell_size;
+    let height = bitmap.height / cell_size;
+    let mut pixels = Vec::with_capacity(width * height);
+    for cy in 0..height {
+        for cx in 0..width {
+            let block = (0..cell_size).flat_map(|dy| {
+                let row = (cy * cell_size + dy) * bitmap.width + cx * cell_size;
+                bitmap.pixels[row..row + cell_size].iter().copied()
+            });
+            let (mut walls, mut marker) = (0, None);
+            for pixel in block {
+                match pixel {
+                    Pixel::Wall => walls += 1,
+                    Pixel::Start | Pixel::End => marker = marker.or(Some(pixel)),
+                    Pixel::Open => {}
+                }
+            }
+            pixels.push(match marker {
+                Some(marker) => marker,
+                None if 2 * walls > cell_size * cell_size => Pixel::Wall,
+                None => Pixel::Open,
+            });
+        }
+    }
+    Bitmap { width, height, pixels }
+}
+
+// Reads a `.png` or `.pbm` maze, one cell per `cell_size` pixels.
+pub fn load(path: &Path, cell_size: usize) -> Result<Maze, String> {
+    let with_path = |e: String| format!("{}: {}", path.display(), e);
+    let bytes = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let bitmap = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("png") => decode_png(&bytes),
+        Some("pbm") => decode_pbm(&bytes),
+        _ => Err("images must end in .png or .pbm".to_string()),
+    };
+    bitmap.and_then(|bitmap| to_maze(&downsample(&bitmap, cell_size), cell_size)).map_err(with_path)
+}
+
+// Builds the maze for a bitmap with one pixel per cell.
+fn to_maze(bitmap: &Bitmap, cell_size: usize) -> Result<Maze, String> {
+    if bitmap.width == 0 || bitmap.height == 0 {
+        return Err(format!("image is smaller than one {}px cell", cell_size));
+    }
+
+    // Dark cells keep all four walls, so they can never be entered.
+    let mut maze = Maze::new(bitmap.width, bitmap.height);
+    for y in 0..bitmap.height {
+        for x in 0..bitmap.width {
+            if bitmap.pixels[y * bitmap.width + x] == Pixel::Wall {
+                continue;
+            }
+            for dir in [1, 2] {
+                if let Some(next) = maze.neighbor((x, y), dir)
+                    && bitmap.pixels[maze.index(next)] != Pixel::Wall
+                {
+                    maze.carve_passage((x, y), dir);
+                }
+            }
+        }
+    }
+
+    let position = |i: usize| (i % bitmap.width, i / bitmap.width);
+    let find = |kind: Pixel| bitmap.pixels.iter().position(|&pixel| pixel == kind).map(position);
+    let first_open = bitmap.pixels.iter().position(|&pixel| pixel != Pixel::Wall).map(position);
+    let last_open = bitmap.pixels.iter().rposition(|&pixel| pixel != Pixel::Wall).map(position);
+    maze.start_point = find(Pixel::Start).or(first_open).ok_or("image has no passages")?;
+    maze.end_point = find(Pixel::End).or(last_open).ok_or("image has no passages")?;
+    Ok(maze)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{Bfs, Pathfinder};
+
+    // A 5x3 maze, one pixel per cell, `#` for wall. The only way from the top-left corner to
+    // the bottom-right one runs down the middle column.
+    const ROWS: [&str; 3] = ["...#.", "##.#.", "....."];
+
+    fn pbm_p1() -> Vec<u8> {
+        let pixels: Vec<String> = ROWS.iter().map(|row| row.chars().map(|c| if c == '#' { "1 " } else { "0 " }).collect()).collect();
+        format!("P1\n# drawn by hand\n5 3\n{}\n", pixels.join("\n")).into_bytes()
+    }
+
+    fn pbm_p4() -> Vec<u8> {
+        let mut bytes = b"P4\n5 3\n".to_vec();
+        bytes.extend(ROWS.iter().map(|row| row.chars().enumerate().fold(0u8, |byte, (x, c)| byte | (((c == '#') as u8) << (7 - x)))));
+        bytes
+    }
+
+    fn decode(bitmap: Result<Bitmap, String>, cell_size: usize) -> Maze {
+        to_maze(&downsample(&bitmap.unwrap(), cell_size), cell_size).unwrap()
+    }
+
+    fn check_known_walls(maze: &Maze) {
+        assert_eq!((maze.width, maze.height), (5, 3));
+        assert!(!maze.has_wall((0, 0), 1));
+        assert!(maze.has_wall((1, 0), 2));
+        assert!(maze.has_wall((2, 0), 1));
+        assert!(!maze.has_wall((4, 0), 2));
+    }
+
+    #[test]
+    fn decodes_ascii_pbm() {
+        let maze = decode(decode_pbm(&pbm_p1()), 1);
+        check_known_walls(&maze);
+        // Without markers the endpoints are the first and last passage cells.
+        assert_eq!((maze.start_point, maze.end_point), ((0, 0), (4, 2)));
+        assert_eq!(Bfs.search(&maze).path, [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (3, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn decodes_packed_pbm_like_ascii() {
+        let packed = decode(decode_pbm(&pbm_p4()), 1);
+        check_known_walls(&packed);
+        assert_eq!(packed.to_text(None), decode(decode_pbm(&pbm_p1()), 1).to_text(None));
+    }
+
+    #[test]
+    fn rejects_truncated_and_unsupported_pbm() {
+        let mut p4 = pbm_p4();
+        p4.pop();
+        assert_eq!(decode_pbm(&p4).err().unwrap(), "PBM pixel data is truncated");
+        let p1 = pbm_p1();
+        assert_eq!(decode_pbm(&p1[..p1.len() - 4]).err().unwrap(), "PBM pixel data is truncated");
+        assert!(decode_pbm(b"P2\n5 3\n").is_err());
+        assert_eq!(decode_pbm(b"P1\n4294967296 4294967297\n0").err().unwrap(), "PBM size is too large");
+        assert_eq!(decode_pbm(format!("P4\n9 {}\n", usize::MAX / 2).as_bytes()).err().unwrap(), "PBM size is too large");
+        assert_eq!(decode_pbm(b"P1\n100000 100000\n0 1").err().unwrap(), "PBM pixel data is truncated");
+    }
+
+    #[test]
+    fn decodes_png_with_markers_and_cell_size() {
+        // Every cell is a 2x2 block; one pixel of green and red marks the start and end.
+        let (width, height) = (10, 6);
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let color = match (x, y) {
+                    (5, 1) => [0, 200, 0],
+                    (8, 0) => [220, 0, 0],
+                    _ if ROWS[y / 2].as_bytes()[x / 2] == b'#' => [20, 20, 20],
+                    _ => [240, 240, 240],
+                };
+                pixels.extend(color);
+            }
+        }
+        let mut png_bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_bytes, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(&pixels).unwrap();
+
+        let maze = decode(decode_png(&png_bytes), 2);
+        check_known_walls(&maze);
+        assert_eq!((maze.start_point, maze.end_point), ((2, 0), (4, 0)));
+        assert_eq!(Bfs.search(&maze).path, [(2, 0), (2, 1), (2, 2), (3, 2), (4, 2), (4, 1), (4, 0)]);
+    }
+}
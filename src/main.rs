@@ -1,68 +1,504 @@
-use std::collections::VecDeque;
+mod benchmark;
+mod binary_format;
+mod bitset;
+mod cli;
+mod dstar_lite;
+mod dynamic;
+mod experiment;
+mod exploration;
+mod export;
+mod generators;
+mod headless;
+mod image_format;
+mod pathfinding;
+mod recorder;
+mod render;
+mod svg;
+mod terrain;
+mod text_format;
+
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::thread::sleep;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use minifb::{Key, Window as MiniFbWindow, WindowOptions};
-use rand::prelude::IteratorRandom;
-use rand::Rng;
-use font8x8::legacy::BASIC_LEGACY;
-use rayon::prelude::*;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use bitset::BitSet;
+use cli::Cli;
+use exploration::{DfsExplorer, FrontierExplorer};
+use export::RunRecord;
+use generators::{Kruskal, MazeGenerator, Prim, RecursiveBacktracker, Wilson};
+use pathfinding::{
+    AStar, Bfs, BidirectionalBfs, DeadEndFilling, Dfs, Dijkstra, GreedyBestFirst, IdaStar, Iddfs, JumpPointSearch, Pathfinder, Pledge, SearchOutcome,
+    Tremaux, WallFollower,
+};
+use render::Canvas;
+use svg::{SvgLayer, SvgRun};
+use terrain::Terrain;
 
 // --- NEW --- An enum to identify the algorithms.
 // To add a new one, just add a variant here (e.g., AStar).
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Algorithm {
     Bfs,
+    BidirectionalBfs,
     Dfs,
+    Dijkstra,
+    AStar(Heuristic),
+    // A* with the Manhattan estimate scaled by the given weight (at least 1).
+    WeightedAStar(f64),
+    GreedyBestFirst,
+    Iddfs,
+    IdaStar,
+    JumpPointSearch,
+    WallFollower(Hand),
+    Pledge,
+    Tremaux,
+    DeadEndFilling,
+    // Fog-of-war explorers (see `exploration`), with their sensor radius in cells.
+    DfsExplorer(usize),
+    FrontierExplorer(usize),
+}
+
+// Distance estimates A* can use to steer towards the end point.
+// `Zero` turns A* into plain uniform-cost search, handy as a baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Heuristic {
+    Manhattan,
+    Euclidean,
+    Chebyshev,
+    Zero,
+}
+
+impl Heuristic {
+    fn estimate(self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let dx = from.0.abs_diff(to.0) as f64;
+        let dy = from.1.abs_diff(to.1) as f64;
+        match self {
+            Heuristic::Manhattan => dx + dy,
+            Heuristic::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Heuristic::Chebyshev => dx.max(dy),
+            Heuristic::Zero => 0.0,
+        }
+    }
+}
+
+// Which hand a wall follower keeps on the wall.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hand {
+    Left,
+    Right,
 }
 
 // --- NEW --- A struct to hold all information related to a specific algorithm.
 // This makes the simulation loop completely dynamic.
 struct AlgorithmInfo {
-    name: &'static str,
-    function: fn(&Maze) -> (usize, u128, Vec<(usize, usize)>, Vec<(usize, usize)>),
+    name: String,
+    // Short identifier matching the `--algo` name, used in file names.
+    id: String,
+    pathfinder: Box<dyn Pathfinder>,
     search_color: u32,
+    // Colour for cells discovered from the end point, for searches that run from both ends.
+    end_search_color: Option<u32>,
     path_color: u32,
 }
 
+impl AlgorithmInfo {
+    // Colours for each visit event, indexed by `VisitKind`.
+    fn visit_colors(&self) -> [u32; 4] {
+        [
+            self.search_color,
+            self.end_search_color.unwrap_or(self.search_color),
+            render::FILLED_COLOR,
+            render::JUMP_POINT_COLOR,
+        ]
+    }
+}
+
 // --- NEW --- A centralized place to define the properties of each algorithm.
 // To add a new algorithm, just add a match arm here.
 fn get_algorithm_info(algo: Algorithm) -> AlgorithmInfo {
     match algo {
         Algorithm::Bfs => AlgorithmInfo {
-            name: "BFS",
-            function: Maze::path_finding_bfs,
+            name: "BFS".into(),
+            id: "bfs".into(),
+            pathfinder: Box::new(Bfs),
             search_color: 0xAA0000FF, // Blueish search
+            end_search_color: None,
             path_color: 0xAAFFFF00,   // Yellow path
         },
+        Algorithm::BidirectionalBfs => AlgorithmInfo {
+            name: "Bidirectional BFS".into(),
+            id: "bidirectional-bfs".into(),
+            pathfinder: Box::new(BidirectionalBfs),
+            search_color: 0xAA8000FF,           // Purple search from the start
+            end_search_color: Some(0xAA00FF80), // Mint search from the end
+            path_color: 0xAAFFFFFF,             // White path
+        },
         Algorithm::Dfs => AlgorithmInfo {
-            name: "DFS",
-            function: Maze::path_finding_dfs,
+            name: "DFS".into(),
+            id: "dfs".into(),
+            pathfinder: Box::new(Dfs),
             search_color: 0xAA00FFFF, // Cyan search
+            end_search_color: None,
             path_color: 0xAAFF00FF,   // Magenta path
         },
+        Algorithm::Dijkstra => AlgorithmInfo {
+            name: "Dijkstra".into(),
+            id: "dijkstra".into(),
+            pathfinder: Box::new(Dijkstra),
+            search_color: 0xAAC04040, // Brick search
+            end_search_color: None,
+            path_color: 0xAA80FFFF,   // Light cyan path
+        },
+        Algorithm::AStar(heuristic) => AlgorithmInfo {
+            name: match heuristic {
+                Heuristic::Manhattan => "A* (Manhattan)",
+                Heuristic::Euclidean => "A* (Euclidean)",
+                Heuristic::Chebyshev => "A* (Chebyshev)",
+                Heuristic::Zero => "A* (Zero)",
+            }.into(),
+            id: match heuristic {
+                Heuristic::Manhattan => "astar-manhattan",
+                Heuristic::Euclidean => "astar-euclidean",
+                Heuristic::Chebyshev => "astar-chebyshev",
+                Heuristic::Zero => "astar-zero",
+            }.into(),
+            pathfinder: Box::new(AStar { heuristic, weight: 1.0 }),
+            search_color: 0xAAFF8000, // Orange search
+            end_search_color: None,
+            path_color: 0xAA00FF00,   // Green path
+        },
+        Algorithm::WeightedAStar(weight) => AlgorithmInfo {
+            name: format!("Weighted A* (w={})", weight),
+            id: format!("weighted-astar-{}", weight),
+            pathfinder: Box::new(AStar { heuristic: Heuristic::Manhattan, weight }),
+            search_color: 0xAAFFC000, // Amber search
+            end_search_color: None,
+            path_color: 0xAA00A0FF,   // Sky blue path
+        },
+        Algorithm::GreedyBestFirst => AlgorithmInfo {
+            name: "Greedy Best-First".into(),
+            id: "greedy".into(),
+            pathfinder: Box::new(GreedyBestFirst { heuristic: Heuristic::Manhattan }),
+            search_color: 0xAAFF4080, // Rose search
+            end_search_color: None,
+            path_color: 0xAAC0FF40,   // Lime path
+        },
+        Algorithm::Iddfs => AlgorithmInfo {
+            name: "IDDFS".into(),
+            id: "iddfs".into(),
+            pathfinder: Box::new(Iddfs),
+            search_color: 0xAA00C0A0, // Teal-green search
+            end_search_color: None,
+            path_color: 0xAAFF8080,   // Salmon path
+        },
+        Algorithm::IdaStar => AlgorithmInfo {
+            name: "IDA* (Manhattan)".into(),
+            id: "idastar".into(),
+            pathfinder: Box::new(IdaStar { heuristic: Heuristic::Manhattan }),
+            search_color: 0xAAC06000, // Rust search
+            end_search_color: None,
+            path_color: 0xAA80FF80,   // Pale green path
+        },
+        Algorithm::JumpPointSearch => AlgorithmInfo {
+            name: "Jump Point Search".into(),
+            id: "jps".into(),
+            pathfinder: Box::new(JumpPointSearch),
+            // Cells jumped over; the jump points themselves use `render::JUMP_POINT_COLOR`.
+            search_color: 0xAA305070, // Slate search
+            end_search_color: None,
+            path_color: 0xAAFFFF80,   // Pale yellow path
+        },
+        Algorithm::WallFollower(hand) => AlgorithmInfo {
+            name: match hand {
+                Hand::Left => "Wall Follower (L)",
+                Hand::Right => "Wall Follower (R)",
+            }.into(),
+            id: match hand {
+                Hand::Left => "wall-follower-left",
+                Hand::Right => "wall-follower-right",
+            }.into(),
+            pathfinder: Box::new(WallFollower { hand }),
+            search_color: 0xAA808000, // Olive search
+            end_search_color: None,
+            path_color: 0xAAFFD700,   // Gold path
+        },
+        Algorithm::Pledge => AlgorithmInfo {
+            name: "Pledge".into(),
+            id: "pledge".into(),
+            pathfinder: Box::new(Pledge),
+            search_color: 0xAA008080, // Teal search
+            end_search_color: None,
+            path_color: 0xAAFF60C0,   // Pink path
+        },
+        Algorithm::Tremaux => AlgorithmInfo {
+            name: "Tremaux".into(),
+            id: "tremaux".into(),
+            pathfinder: Box::new(Tremaux),
+            search_color: 0xAA6040C0, // Violet search
+            end_search_color: None,
+            path_color: 0xAAFFA040,   // Orange path
+        },
+        Algorithm::DeadEndFilling => AlgorithmInfo {
+            name: "Dead-End Filling".into(),
+            id: "dead-end-filling".into(),
+            pathfinder: Box::new(DeadEndFilling),
+            // Only fills cells, which are drawn in `render::FILLED_COLOR`.
+            search_color: 0xAA404040,
+            end_search_color: None,
+            path_color: 0xAA40FFC0,   // Mint path
+        },
+        Algorithm::DfsExplorer(sensor_radius) => AlgorithmInfo {
+            name: format!("DFS Explorer (r={})", sensor_radius),
+            id: format!("dfs-explorer-r{}", sensor_radius),
+            pathfinder: Box::new(DfsExplorer { sensor_radius }),
+            search_color: 0xAA8040FF, // Indigo search
+            end_search_color: None,
+            path_color: 0xAAFFE0A0,   // Sand path
+        },
+        Algorithm::FrontierExplorer(sensor_radius) => AlgorithmInfo {
+            name: format!("Frontier Explorer (r={})", sensor_radius),
+            id: format!("frontier-explorer-r{}", sensor_radius),
+            pathfinder: Box::new(FrontierExplorer { sensor_radius }),
+            search_color: 0xAA40A0C0, // Steel blue search
+            end_search_color: None,
+            path_color: 0xAAFF80FF,   // Orchid path
+        },
     }
 }
 
 // --- NEW --- A struct to hold the results for cleaner data management.
 struct PathfindingResult {
-    name: &'static str,
+    name: String,
     color: u32,
     steps: usize,
-    duration: u128,
+    reexpanded: usize,
+    frontier_peak: usize,
+    duration: Duration,
+    found: bool,
+    // The search gave up going in circles, see `SearchOutcome::looped`.
+    looped: bool,
+    // The search ran out of expansion budget, see `SearchOutcome::gave_up`.
+    gave_up: bool,
     path_len: usize,
+    // Summed terrain cost of the path; `path_len - 1` on mazes without terrain.
+    path_cost: usize,
+    // The best path on the same maze, when its algorithm was one of those run.
+    reference: Option<Reference>,
+    visited: usize,
+}
+
+impl PathfindingResult {
+    fn new(info: &AlgorithmInfo, outcome: &SearchOutcome, maze: &Maze, reference: Option<Reference>) -> Self {
+        Self {
+            name: info.name.clone(),
+            color: info.path_color,
+            steps: outcome.expanded,
+            reexpanded: outcome.reexpanded,
+            frontier_peak: outcome.frontier_peak,
+            duration: outcome.elapsed,
+            found: outcome.found,
+            looped: outcome.looped,
+            gave_up: outcome.gave_up,
+            path_len: outcome.path.len(),
+            path_cost: maze.path_cost(&outcome.path),
+            reference,
+            visited: outcome.visits.len(),
+        }
+    }
+
+    // How much longer the path is than the reference, or on terrain how much more expensive, in
+    // percent. `None` without a path or a reference.
+    fn excess(&self) -> Option<f64> {
+        let reference = self.reference.filter(|_| self.found)?;
+        let value = if reference.weighted { self.path_cost } else { self.path_len };
+        // Only a path that starts on the end point costs nothing, and then every path does.
+        if reference.value == 0 {
+            return Some(0.0);
+        }
+        Some((value as f64 / reference.value as f64 - 1.0) * 100.0)
+    }
+}
+
+// The best path other results are compared against: the one with the fewest cells, found by
+// BFS, or on mazes with terrain, where a short path can still wade through water, the cheapest
+// one, found by Dijkstra.
+#[derive(Clone, Copy)]
+struct Reference {
+    weighted: bool,
+    // Path length from BFS, or path cost from Dijkstra.
+    value: usize,
+}
+
+impl Reference {
+    fn name(self) -> &'static str {
+        if self.weighted { "Dijkstra" } else { "BFS" }
+    }
+}
+
+// Only available when the reference algorithm is one of the configured ones, so runs without
+// it don't pay for an extra search.
+fn reference_path(config: &Config, maze: &Maze) -> Option<Reference> {
+    let weighted = maze.is_weighted();
+    let (algorithm, pathfinder): (Algorithm, &dyn Pathfinder) = if weighted { (Algorithm::Dijkstra, &Dijkstra) } else { (Algorithm::Bfs, &Bfs) };
+    if !config.algorithms_to_run.contains(&algorithm) {
+        return None;
+    }
+    let outcome = pathfinder.search(maze);
+    let value = if weighted { maze.path_cost(&outcome.path) } else { outcome.path.len() };
+    outcome.found.then_some(Reference { weighted, value })
+}
+
+// Identifies the algorithm used to carve the maze before any loops are added.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Generator {
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Wilson,
+}
+
+impl Generator {
+    fn name(self) -> &'static str {
+        match self {
+            Generator::RecursiveBacktracker => "Recursive Backtracker",
+            Generator::Prim => "Randomized Prim",
+            Generator::Kruskal => "Randomized Kruskal",
+            Generator::Wilson => "Wilson",
+        }
+    }
+
+    fn implementation(self) -> Box<dyn MazeGenerator> {
+        match self {
+            Generator::RecursiveBacktracker => Box::new(RecursiveBacktracker),
+            Generator::Prim => Box::new(Prim),
+            Generator::Kruskal => Box::new(Kruskal),
+            Generator::Wilson => Box::new(Wilson),
+        }
+    }
+}
+
+// File format for saved mazes. Loading picks the format from the file extension instead
+// (see `read_maze_file`).
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum MazeFormat {
+    // ASCII art with a readable header, see `text_format`.
+    Text,
+    // Two bits per cell with a checksum, see `binary_format`.
+    Binary,
+}
+
+impl MazeFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            MazeFormat::Text => "maze",
+            MazeFormat::Binary => "mazb",
+        }
+    }
+
+    fn save(self, path: &Path, maze: &Maze, seed: Option<u64>) -> Result<(), String> {
+        match self {
+            MazeFormat::Text => text_format::save(path, maze, seed),
+            MazeFormat::Binary => binary_format::save(path, maze, seed),
+        }
+    }
+}
+
+// Reads a maze saved with `--maze-dir` (`.maze` or `.mazb`) or converts a `.png`/`.pbm` bitmap,
+// returning the seed if the file recorded one.
+fn read_maze_file(path: &Path, image_cell_size: usize) -> Result<(Maze, Option<u64>), String> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("mazb") => binary_format::load(path),
+        Some("png" | "pbm") => image_format::load(path, image_cell_size).map(|maze| (maze, None)),
+        _ => text_format::load(path),
+    }
+}
+
+// A maze read with `--load-maze`. It is parsed once and copied for every run that uses it.
+#[derive(Clone)]
+struct LoadedMaze {
+    path: PathBuf,
+    maze: Maze,
+    // Seed recorded in the file, if its format stores one.
+    seed: Option<u64>,
+}
+
+impl LoadedMaze {
+    fn read(path: PathBuf, image_cell_size: usize) -> Result<Self, String> {
+        let (maze, seed) = read_maze_file(&path, image_cell_size)?;
+        Ok(Self { path, maze, seed })
+    }
+}
+
+// How many mazes and repeated runs per maze a benchmark collects statistics over.
+#[derive(Clone, Copy)]
+struct BenchmarkPlan {
+    mazes: usize,
+    repetitions: usize,
+}
+
+// How often and how much the maze changes while the agent walks in dynamic mode.
+#[derive(Clone, Copy)]
+struct DynamicPlan {
+    // Walls opened or closed per change.
+    toggles: usize,
+    // Agent steps between changes.
+    interval: usize,
 }
 
 // --- CONFIGURATION ---
+#[derive(Clone)]
 struct Config {
     screen_width: usize,
     screen_height: usize,
     use_perfect_maze: bool,
+    // Fraction of the cell count knocked out as extra walls when the maze is not perfect.
+    loop_percentage: f32,
+    generator: Generator,
     skip_visualization: bool,
+    // Never opens a window: results are printed to stdout instead (see `headless::run`).
+    headless: bool,
+    // When set, runs the statistical benchmark (see `benchmark::run`) instead of a single pass.
+    benchmark: Option<BenchmarkPlan>,
+    // When set, walks an agent through a changing maze with D* Lite (see `dynamic::run`).
+    dynamic: Option<DynamicPlan>,
     maze_width: usize,
     maze_height: usize,
+    // Override the default corners (or a loaded maze's endpoints). An end point away from the
+    // outer wall is what defeats wall followers on mazes with loops.
+    start_point: Option<(usize, usize)>,
+    end_point: Option<(usize, usize)>,
+    // Paints mud and water regions over the maze (see `terrain::paint`), making cells cost
+    // different amounts to cross.
+    terrain: bool,
     batch_size: usize,
     target_fps: u64,
+    // Seed for every random choice in a run. `None` picks a fresh one; the seed actually
+    // used is shown on the stats screen so the run can be replayed with `Some(seed)`.
+    seed: Option<u64>,
+    // When set, every run also writes `seed-<seed>-<algorithm>.png` into this directory.
+    png_dir: Option<PathBuf>,
+    // Pixels per maze cell in exported images.
+    png_cell_size: usize,
+    // When set, the maze (and each run, depending on `svg_layers`) is also saved as SVG here.
+    svg_dir: Option<PathBuf>,
+    svg_layers: Vec<SvgLayer>,
+    // When set, every run is also recorded as `seed-<seed>-<algorithm>.gif` here, one frame per `batch_size` cells.
+    gif_dir: Option<PathBuf>,
+    gif_cell_size: usize,
+    // When set, every maze is also saved here as `seed-<seed>.maze` or `.mazb`, depending on `maze_format`.
+    maze_dir: Option<PathBuf>,
+    maze_format: MazeFormat,
+    // When set, every run uses this maze, read from a file, instead of generating one; width,
+    // height and seed come from the file.
+    load_maze: Option<LoadedMaze>,
+    // Image pixels per maze cell when `load_maze` is a bitmap.
+    image_cell_size: usize,
     // --- CHANGED --- We now use a Vec to hold the sequence of algorithms to run.
     algorithms_to_run: Vec<Algorithm>,
 }
@@ -73,235 +509,347 @@ impl Default for Config {
             screen_width: 1920,
             screen_height: 1080,
             use_perfect_maze: false,
+            loop_percentage: 0.08,
+            generator: Generator::RecursiveBacktracker,
             skip_visualization: false,
+            headless: false,
+            benchmark: None,
+            dynamic: None,
             maze_width: 240,
             maze_height: 140,
+            start_point: None,
+            end_point: None,
+            terrain: false,
             batch_size: 40,
             target_fps: 60,
+            seed: None,
+            png_dir: None,
+            png_cell_size: 8,
+            svg_dir: None,
+            svg_layers: vec![SvgLayer::Markers, SvgLayer::Path],
+            gif_dir: None,
+            gif_cell_size: 4,
+            maze_dir: None,
+            maze_format: MazeFormat::Text,
+            load_maze: None,
+            image_cell_size: 1,
             // --- CHANGED --- Default is now a vector.
-            algorithms_to_run: vec![Algorithm::Bfs, Algorithm::Dfs],
+            algorithms_to_run: vec![Algorithm::Bfs, Algorithm::Dfs, Algorithm::AStar(Heuristic::Manhattan)],
         }
     }
 }
 
+impl Config {
+    fn resolve_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| rand::rng().random())
+    }
 
-#[derive(Clone, Copy)]
-struct Cell {
-    walls: [bool; 4],
-    visited: bool,
+    // Name of the generator that carved the maze, or `None` for a loaded maze.
+    fn generator_label(&self) -> Option<&'static str> {
+        self.load_maze.is_none().then(|| self.generator.name())
+    }
+
+    // Whether the maze is the same whatever the seed: a loaded maze without terrain.
+    fn maze_is_fixed(&self) -> bool {
+        self.load_maze.is_some() && !self.terrain
+    }
+
+    // The seed that reproduces the maze built from the run's `seed`. A fixed maze keeps the
+    // seed its file recorded, if any.
+    fn maze_seed(&self, seed: u64) -> Option<u64> {
+        match &self.load_maze {
+            Some(loaded) if self.maze_is_fixed() => loaded.seed,
+            _ => Some(seed),
+        }
+    }
+
+    fn seed_label(&self, seed: u64) -> String {
+        match self.maze_seed(seed) {
+            Some(seed) => seed.to_string(),
+            None => "none (not recorded in the file)".to_string(),
+        }
+    }
+
+    fn maze_type_label(&self) -> String {
+        if let Some(loaded) = &self.load_maze {
+            format!("Loaded from {}", loaded.path.display())
+        } else if self.use_perfect_maze {
+            "Perfect (No Loops)".to_string()
+        } else {
+            format!("Imperfect (With Loops, {:.0}%)", self.loop_percentage * 100.0)
+        }
+    }
+}
+
+// Creates `dir` if needed and hands `write` the full path of `file_name` inside it.
+// Exits with an error message if either step fails.
+fn write_export_file(dir: &Path, file_name: String, write: impl FnOnce(&Path) -> Result<(), String>) {
+    let path = dir.join(file_name);
+    let result = fs::create_dir_all(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))
+        .and_then(|_| write(&path));
+    if let Err(message) = result {
+        eprintln!("error: failed to export: {}", message);
+        std::process::exit(1);
+    }
+}
+
+// Renders a finished search offscreen and saves it as a PNG if `png_dir` is set.
+fn export_png(config: &Config, seed: u64, maze: &Maze, info: &AlgorithmInfo, outcome: &SearchOutcome) {
+    let Some(dir) = &config.png_dir else { return };
+    let canvas = render::render_outcome(maze, outcome, info.visit_colors(), info.path_color, config.png_cell_size);
+    write_export_file(dir, format!("seed-{}-{}.png", seed, info.id), |path| canvas.save_png(path));
+}
+
+// Replays a finished search offscreen into an animated GIF if `gif_dir` is set.
+fn export_gif(config: &Config, seed: u64, maze: &Maze, info: &AlgorithmInfo, outcome: &SearchOutcome) {
+    let Some(dir) = &config.gif_dir else { return };
+    write_export_file(dir, format!("seed-{}-{}.gif", seed, info.id), |path| {
+        recorder::record_search_gif(
+            path,
+            maze,
+            outcome,
+            info.visit_colors(),
+            info.path_color,
+            config.gif_cell_size,
+            config.batch_size,
+        )
+    });
 }
 
+// Saves SVGs into `svg_dir` if it is set. Called once per maze with `run: None` and once per
+// algorithm with its outcome: if any per-run layer (explored, path) is selected, each run gets
+// `seed-<seed>-<algorithm>.svg`, otherwise the maze gets a single `seed-<seed>.svg`.
+fn export_svg(config: &Config, seed: u64, maze: &Maze, run: Option<(&AlgorithmInfo, &SearchOutcome)>) {
+    let Some(dir) = &config.svg_dir else { return };
+    let per_run = config.svg_layers.iter().any(|layer| matches!(layer, SvgLayer::Explored | SvgLayer::Path));
+    let (file_name, svg_run) = match (per_run, run) {
+        (false, None) => (format!("seed-{}.svg", seed), None),
+        (true, Some((info, outcome))) => (
+            format!("seed-{}-{}.svg", seed, info.id),
+            Some(SvgRun {
+                visits: &outcome.visits,
+                path: &outcome.path,
+                visit_colors: info.visit_colors(),
+                path_color: info.path_color,
+            }),
+        ),
+        _ => return,
+    };
+    let svg = svg::maze_to_svg(maze, &config.svg_layers, svg_run.as_ref());
+    write_export_file(dir, file_name, |path| fs::write(path, svg).map_err(|e| format!("{}: {}", path.display(), e)));
+}
+
+// Generates the maze described by `config`, taking every random choice from `seed`, or copies
+// the one in `load_maze`. Paints terrain over it if `terrain` is set, then saves it into
+// `maze_dir` if that is set.
+fn build_maze(config: &Config, seed: u64) -> Maze {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut maze = match &config.load_maze {
+        Some(loaded) => loaded.maze.clone(),
+        None => {
+            let generator = config.generator.implementation();
+            let mut maze = Maze::new(config.maze_width, config.maze_height);
+            if config.use_perfect_maze {
+                maze.generate(generator.as_ref(), &mut rng);
+            } else {
+                maze.generate_with_loops(generator.as_ref(), config.loop_percentage, &mut rng);
+            }
+            maze
+        }
+    };
+    if config.terrain {
+        terrain::paint(&mut maze, &mut rng);
+    }
+    maze.start_point = config.start_point.unwrap_or(maze.start_point);
+    maze.end_point = config.end_point.unwrap_or(maze.end_point);
+    if let Some(dir) = &config.maze_dir {
+        let file_name = format!("seed-{}.{}", seed, config.maze_format.extension());
+        write_export_file(dir, file_name, |path| config.maze_format.save(path, &maze, Some(seed)));
+    }
+    maze
+}
+
+
+// Generators number cells and walls with `u32`s to save memory (see `generators`), which caps
+// a generated maze at 2^30 cells, e.g. 32768x32768.
+const MAX_GENERATED_CELLS: usize = 1 << 30;
+
+// Only each cell's east and south walls are stored, packed two bits per cell; a north or west
+// wall is the south or east wall of the neighbour, and the outer border is always closed. With
+// `visited` in its own bitset a cell costs 3 bits, so a 20000x20000 maze takes about 150 MB.
+// Painted terrain adds a byte per cell.
+#[derive(Clone)]
 struct Maze {
     start_point: (usize, usize),
     end_point: (usize, usize),
     width: usize,
     height: usize,
-    grid: Vec<Cell>,
+    // Bit `2 * index` is the east wall of a cell, bit `2 * index + 1` its south wall.
+    walls: BitSet,
+    // Scratch space for generators.
+    visited: BitSet,
+    // Per-cell terrain, row-major. Empty until painted, meaning every cell is road (cost 1).
+    terrain: Vec<Terrain>,
 }
 
-// ... The Maze implementation remains exactly the same
 impl Maze {
     fn new(width: usize, height: usize) -> Self {
-        let grid = vec![
-            Cell {
-                walls: [true; 4],
-                visited: false
-            };
-            width * height
-        ];
         Maze {
             start_point: (0, 0),
             end_point: (width - 1, height - 1),
             width,
             height,
-            grid,
+            walls: BitSet::new(2 * width * height, true),
+            visited: BitSet::new(width * height, false),
+            terrain: Vec::new(),
         }
     }
 
-    fn generate_iterative(&mut self) {
-        for cell in self.grid.iter_mut() {
-            cell.visited = false;
-        }
+    fn index(&self, (x, y): (usize, usize)) -> usize {
+        y * self.width + x
+    }
 
-        let mut rng = rand::rng();
-        let mut stack = vec![(0, 0)];
-        self.grid[0].visited = true;
-        while let Some((x, y)) = stack.pop() {
-            let mut neighbors = vec![];
-            let directions = [(0, -1), (1, 0), (0, 1), (-1, 0)];
-            for (i, &(dx, dy)) in directions.iter().enumerate() {
-                let nx = x as isize + dx;
-                let ny = y as isize + dy;
-                if nx >= 0 && nx < self.width as isize && ny >= 0 && ny < self.height as isize {
-                    let ni = (ny as usize) * self.width + (nx as usize);
-                    if !self.grid[ni].visited {
-                        neighbors.push((ni, i));
-                    }
-                }
-            }
-            if !neighbors.is_empty() {
-                stack.push((x, y));
-                let &(ni, dir) = neighbors.iter().choose(&mut rng).unwrap();
-                self.grid[ni].visited = true;
-                self.grid[y * self.width + x].walls[dir] = false;
-                self.grid[ni].walls[(dir + 2) % 4] = false;
-                let nx = ni % self.width;
-                let ny = ni / self.width;
-                stack.push((nx, ny));
-            }
-        }
+    // The cell at row-major `index`, the inverse of `Maze::index`.
+    fn position(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
     }
 
-    fn generate_with_loops(&mut self) {
-        self.generate_iterative();
+    // The in-bounds cell one step from `(x, y)` in direction `dir` (0 = up, 1 = right, 2 = down, 3 = left).
+    fn neighbor(&self, (x, y): (usize, usize), dir: usize) -> Option<(usize, usize)> {
+        let (dx, dy) = [(0, -1), (1, 0), (0, 1), (-1, 0)][dir];
+        let nx = x.checked_add_signed(dx)?;
+        let ny = y.checked_add_signed(dy)?;
+        (nx < self.width && ny < self.height).then_some((nx, ny))
+    }
 
-        let mut rng = rand::rng();
-        let loop_percentage = 0.08;
-        let walls_to_remove = ((self.width * self.height) as f32 * loop_percentage) as usize;
+    // All in-bounds neighbours of `(x, y)`, walls or not, paired with their direction index.
+    fn neighbors(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, (usize, usize))> + '_ {
+        (0..4).filter_map(move |dir| self.neighbor(position, dir).map(|next| (dir, next)))
+    }
 
-        for _ in 0..walls_to_remove {
-            let x = rng.random_range(0..self.width.saturating_sub(1));
-            let y = rng.random_range(0..self.height.saturating_sub(1));
-            let current_idx = y * self.width + x;
+    // Neighbouring cells reachable from `(x, y)` without crossing a wall.
+    fn open_neighbors(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, (usize, usize))> + '_ {
+        self.neighbors(position).filter(move |&(dir, _)| !self.has_wall(position, dir))
+    }
 
-            if rng.random_bool(0.5) {
-                let neighbor_idx = y * self.width + (x + 1);
-                if self.grid[current_idx].walls[1] {
-                    self.grid[current_idx].walls[1] = false;
-                    self.grid[neighbor_idx].walls[3] = false;
-                }
-            } else {
-                let neighbor_idx = (y + 1) * self.width + x;
-                if self.grid[current_idx].walls[2] {
-                    self.grid[current_idx].walls[2] = false;
-                    self.grid[neighbor_idx].walls[0] = false;
-                }
-            }
+    // The bit holding the wall between `(x, y)` and its neighbour in direction `dir`, or `None`
+    // on the outer border.
+    fn wall_bit(&self, position: (usize, usize), dir: usize) -> Option<usize> {
+        let next = self.neighbor(position, dir)?;
+        Some(match dir {
+            1 => 2 * self.index(position),
+            2 => 2 * self.index(position) + 1,
+            0 => 2 * self.index(next) + 1,
+            _ => 2 * self.index(next),
+        })
+    }
+
+    fn has_wall(&self, position: (usize, usize), dir: usize) -> bool {
+        self.wall_bit(position, dir).is_none_or(|bit| self.walls.get(bit))
+    }
+
+    // All four walls of `(x, y)`, indexed by direction.
+    fn walls(&self, position: (usize, usize)) -> [bool; 4] {
+        [0, 1, 2, 3].map(|dir| self.has_wall(position, dir))
+    }
+
+    // Opens or closes the wall between `(x, y)` and its neighbour in direction `dir`. The outer
+    // border cannot be changed.
+    fn set_wall(&mut self, position: (usize, usize), dir: usize, wall: bool) {
+        if let Some(bit) = self.wall_bit(position, dir) {
+            self.walls.set(bit, wall);
         }
     }
 
-    fn path_finding_dfs(&self) -> (usize, u128, Vec<(usize, usize)>, Vec<(usize, usize)>) {
-        let start_time = Instant::now();
-        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; self.width * self.height];
-        let mut stack = vec![self.start_point];
-        let mut visited_for_dfs: Vec<bool> = vec![false; self.width * self.height];
-        let mut entire_path = Vec::new();
+    // Removes the wall between `(x, y)` and its neighbour in direction `dir`.
+    fn carve_passage(&mut self, position: (usize, usize), dir: usize) {
+        self.set_wall(position, dir, false);
+    }
 
-        let start_idx = self.start_point.1 * self.width + self.start_point.0;
-        visited_for_dfs[start_idx] = true;
-        entire_path.push(self.start_point);
-        came_from[start_idx] = Some(self.start_point);
+    fn is_visited(&self, position: (usize, usize)) -> bool {
+        self.visited.get(self.index(position))
+    }
 
-        let mut steps = 0;
+    fn mark_visited(&mut self, position: (usize, usize)) {
+        self.visited.set(self.index(position), true);
+    }
 
-        while let Some((x, y)) = stack.pop() {
-            steps += 1;
-            if (x, y) == self.end_point {
-                break;
-            }
+    fn terrain(&self, position: (usize, usize)) -> Terrain {
+        self.terrain.get(self.index(position)).copied().unwrap_or(Terrain::Road)
+    }
 
-            let directions = [(0, -1), (1, 0), (0, 1), (-1, 0)];
-            for (i, &(dx, dy)) in directions.iter().enumerate() {
-                let nx = x as isize + dx;
-                let ny = y as isize + dy;
-
-                if nx >= 0 && nx < self.width as isize && ny >= 0 && ny < self.height as isize {
-                    let nx = nx as usize;
-                    let ny = ny as usize;
-                    let current_idx = y * self.width + x;
-                    let neighbor_idx = ny * self.width + nx;
-
-                    if !self.grid[current_idx].walls[i] && !visited_for_dfs[neighbor_idx] {
-                        visited_for_dfs[neighbor_idx] = true;
-                        came_from[neighbor_idx] = Some((x, y));
-                        stack.push((nx, ny));
-                        entire_path.push((nx, ny));
-                    }
-                }
-            }
+    fn set_terrain(&mut self, position: (usize, usize), terrain: Terrain) {
+        if self.terrain.is_empty() {
+            self.terrain = vec![Terrain::Road; self.width * self.height];
         }
+        let idx = self.index(position);
+        self.terrain[idx] = terrain;
+    }
 
-        let mut path = Vec::new();
-        let mut current = self.end_point;
-        while current != self.start_point {
-            path.push(current);
-            if let Some(prev) = came_from[current.1 * self.width + current.0] {
-                if prev == current { break; }
-                current = prev;
-            } else {
-                break;
-            }
-        }
-        path.push(self.start_point);
-        path.reverse();
+    // Whether any cell has been given terrain, so costs can differ from hop counts.
+    fn is_weighted(&self) -> bool {
+        !self.terrain.is_empty()
+    }
 
-        let duration = start_time.elapsed().as_millis();
-        (steps, duration, path, entire_path)
+    // Cost of stepping into `(x, y)` from a neighbour.
+    fn cost(&self, position: (usize, usize)) -> usize {
+        self.terrain(position).cost()
     }
 
-    fn path_finding_bfs(&self) -> (usize, u128, Vec<(usize, usize)>, Vec<(usize, usize)>) {
-        let start_time = Instant::now();
-        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; self.width * self.height];
-        let mut queue = VecDeque::new();
-        let mut entire_path = Vec::new();
+    // Total cost of walking `path`: every cell but the first is entered once.
+    fn path_cost(&self, path: &[(usize, usize)]) -> usize {
+        path.iter().skip(1).map(|&cell| self.cost(cell)).sum()
+    }
 
-        queue.push_back(self.start_point);
-        let start_idx = self.start_point.1 * self.width + self.start_point.0;
-        came_from[start_idx] = Some(self.start_point);
-        entire_path.push(self.start_point);
+    // Closes every wall and runs `generator` to carve a perfect maze.
+    fn generate(&mut self, generator: &dyn MazeGenerator, rng: &mut dyn RngCore) {
+        self.walls.fill(true);
+        self.visited.fill(false);
+        self.terrain.clear();
+        generator.carve(self, rng);
+    }
 
-        let mut steps = 0;
+    fn generate_with_loops(&mut self, generator: &dyn MazeGenerator, loop_percentage: f32, rng: &mut dyn RngCore) {
+        self.generate(generator, rng);
 
-        while let Some((x, y)) = queue.pop_front() {
-            steps += 1;
-            if (x, y) == self.end_point {
-                break;
-            }
-            let directions = [(0, -1), (1, 0), (0, 1), (-1, 0)];
-            for (i, &(dx, dy)) in directions.iter().enumerate() {
-                let nx = x as isize + dx;
-                let ny = y as isize + dy;
-                if nx >= 0 && nx < self.width as isize && ny >= 0 && ny < self.height as isize {
-                    let nx = nx as usize;
-                    let ny = ny as usize;
-                    if !self.grid[y * self.width + x].walls[i] {
-                        if came_from[ny * self.width + nx].is_none() {
-                            came_from[ny * self.width + nx] = Some((x, y));
-                            queue.push_back((nx, ny));
-                            entire_path.push((nx, ny));
-                        }
-                    }
-                }
-            }
+        // A single row or column is already just a corridor, there is no wall that could close a loop.
+        if self.width < 2 || self.height < 2 {
+            return;
         }
 
-        let mut path = Vec::new();
-        let mut current = self.end_point;
-        while current != self.start_point {
-            path.push(current);
-             if let Some(prev) = came_from[current.1 * self.width + current.0] {
-                if prev == current { break; }
-                current = prev;
-            } else {
-                break;
-            }
-        }
-        path.push(self.start_point);
-        path.reverse();
+        let walls_to_remove = ((self.width * self.height) as f32 * loop_percentage) as usize;
 
-        let duration = start_time.elapsed().as_millis();
-        (steps, duration, path, entire_path)
+        for _ in 0..walls_to_remove {
+            let x = rng.random_range(0..self.width.saturating_sub(1));
+            let y = rng.random_range(0..self.height.saturating_sub(1));
+            let dir = if rng.random_bool(0.5) { 1 } else { 2 };
+            self.carve_passage((x, y), dir);
+        }
     }
+
 }
 
 
+// A maze with loops, from a fixed seed, for the tests of the file formats. With `terrain` it
+// also gets painted terrain; the endpoints are moved off the corners so they round-trip too.
+#[cfg(test)]
+fn test_maze(width: usize, height: usize, terrain: bool) -> Maze {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut maze = Maze::new(width, height);
+    maze.generate_with_loops(&RecursiveBacktracker, 0.1, &mut rng);
+    if terrain {
+        terrain::paint(&mut maze, &mut rng);
+    }
+    maze.start_point = (1, 0);
+    maze.end_point = (width - 2, height - 1);
+    maze
+}
+
 struct Visualization<'a> {
     window: MiniFbWindow,
-    buffer: Vec<u32>,
+    canvas: Canvas,
     config: &'a Config,
-    cell_size: usize,
-    offset_x: usize,
-    offset_y: usize,
 }
 
 // ... Visualization struct has minor changes, mostly simplification
@@ -313,108 +861,32 @@ impl<'a> Visualization<'a> {
             config.screen_height,
             WindowOptions::default(),
         )
-        .unwrap();
+        .unwrap_or_else(|e| {
+            eprintln!("error: cannot open a window ({}); use --headless", e);
+            std::process::exit(1);
+        });
         window.set_target_fps(config.target_fps as usize);
 
-        let buffer = vec![0; config.screen_width * config.screen_height];
-
-        let min_margin = 50;
-        let max_cell_width = (config.screen_width - 2 * min_margin) / config.maze_width;
-        let max_cell_height = (config.screen_height - 2 * min_margin) / config.maze_height;
-        let cell_size = max_cell_width.min(max_cell_height).max(1);
-
-        let maze_width_px = config.maze_width * cell_size;
-        let maze_height_px = config.maze_height * cell_size;
-        let offset_x = (config.screen_width.saturating_sub(maze_width_px)) / 2;
-        let offset_y = (config.screen_height.saturating_sub(maze_height_px)) / 2;
-
         Self {
             window,
-            buffer,
+            canvas: Canvas::fit_to_screen(config.screen_width, config.screen_height, config.maze_width, config.maze_height),
             config,
-            cell_size,
-            offset_x,
-            offset_y,
-        }
-    }
-
-    fn draw_char(&mut self, x: usize, y: usize, c: char, color: u32) {
-        if let Some(bitmap) = BASIC_LEGACY.get(c as usize) {
-            for (row, bits) in bitmap.iter().enumerate() {
-                for col in 0..8 {
-                    if (bits >> col) & 1 == 1 {
-                        let px = x + col;
-                        let py = y + row;
-                        if px < self.config.screen_width && py < (self.buffer.len() / self.config.screen_width) {
-                            self.buffer[py * self.config.screen_width + px] = color;
-                        }
-                    }
-                }
-            }
         }
     }
 
     fn draw_text(&mut self, x: usize, y: usize, text: &str, color: u32) {
-        for (i, c) in text.chars().enumerate() {
-            self.draw_char(x + i * 8, y, c, color);
-        }
+        self.canvas.draw_text(x, y, text, color);
     }
     
     fn draw_maze(&mut self, maze: &Maze) {
-        self.buffer.fill(0x00101020);
-        let wall_color = 0xFF808080;
-        let maze_width_px = self.config.maze_width * self.cell_size;
-        let maze_height_px = self.config.maze_height * self.cell_size;
-
-        self.buffer
-            .par_chunks_mut(self.config.screen_width)
-            .enumerate()
-            .for_each(|(y_pixel, row_slice)| {
-                if y_pixel < self.offset_y || y_pixel >= self.offset_y + maze_height_px { return; }
-                let y_cell = (y_pixel - self.offset_y) / self.cell_size;
-                if y_cell >= maze.height { return; }
-
-                for (x_pixel_in_row, pixel) in row_slice.iter_mut().enumerate() {
-                    let x_pixel = x_pixel_in_row;
-                    if x_pixel < self.offset_x || x_pixel >= self.offset_x + maze_width_px { continue; }
-                    let x_cell = (x_pixel - self.offset_x) / self.cell_size;
-                    if x_cell >= maze.width { continue; }
-
-                    let inner_x = (x_pixel - self.offset_x) % self.cell_size;
-                    let inner_y = (y_pixel - self.offset_y) % self.cell_size;
-
-                    let cell = &maze.grid[y_cell * maze.width + x_cell];
-
-                    let mut is_wall = false;
-                    if cell.walls[0] && inner_y == 0 { is_wall = true; }
-                    if cell.walls[1] && inner_x == self.cell_size - 1 { is_wall = true; }
-                    if cell.walls[2] && inner_y == self.cell_size - 1 { is_wall = true; }
-                    if cell.walls[3] && inner_x == 0 { is_wall = true; }
-
-                    if is_wall { *pixel = wall_color; }
-                }
-            });
-
-        self.draw_path(&[maze.start_point], 0x0000FF00, false);
-        self.draw_path(&[maze.end_point], 0x00FF0000, false);
+        self.canvas.draw_maze(maze);
         self.update_screen();
     }
 
 
     fn draw_path(&mut self, path: &[(usize, usize)], color: u32, slow_draw: bool) {
-        let path_size = (self.cell_size / 2).max(1);
-        let path_offset = (self.cell_size - path_size) / 2;
-        for &(x, y) in path {
-            for dy in 0..path_size {
-                for dx in 0..path_size {
-                    let px = self.offset_x + x * self.cell_size + path_offset + dx;
-                    let py = self.offset_y + y * self.cell_size + path_offset + dy;
-                    let idx = py * self.config.screen_width + px;
-                    if idx < self.buffer.len() {
-                        self.buffer[idx] = color;
-                    }
-                }
-            }
+        for &cell in path {
+            self.canvas.fill_cell(cell, color);
             if slow_draw {
                 self.update_screen();
                 sleep(Duration::from_micros(100));
@@ -425,22 +897,14 @@ impl<'a> Visualization<'a> {
         }
     }
 
-    fn draw_search_animation(&mut self, entire_path: &[(usize, usize)], color: u32, title: &str) {
+    // Animates every visit event in `outcome`, coloured by its kind: discovered from the start,
+    // discovered from the end point, filled in, or a jump point.
+    fn draw_search_animation(&mut self, outcome: &SearchOutcome, colors: [u32; 4], title: &str) {
         self.draw_text(10, 10, title, 0xFFFFFFFF);
-        let path_size = (self.cell_size / 2).max(1);
-        let path_offset = (self.cell_size - path_size) / 2;
         let mut batch_counter = 0;
 
-        for &(x, y) in entire_path {
-            for dy in 0..path_size {
-                for dx in 0..path_size {
-                    let px = self.offset_x + x * self.cell_size + path_offset + dx;
-                    let py = self.offset_y + y * self.cell_size + path_offset + dy;
-                    if let Some(pixel) = self.buffer.get_mut(py * self.config.screen_width + px) {
-                        *pixel = color;
-                    }
-                }
-            }
+        for &visit in &outcome.visits {
+            self.canvas.draw_visit(visit, colors);
             batch_counter += 1;
             if batch_counter >= self.config.batch_size {
                 self.update_screen();
@@ -452,30 +916,37 @@ impl<'a> Visualization<'a> {
     }
 
     fn update_screen(&mut self) {
-        self.window.update_with_buffer(&self.buffer, self.config.screen_width, self.config.screen_height).unwrap();
+        self.window.update_with_buffer(&self.canvas.buffer, self.canvas.width, self.canvas.height).unwrap();
     }
 }
 
 
 struct Simulation<'a> {
     config: &'a Config,
+    seed: u64,
     maze: Maze,
     viz: Visualization<'a>,
     maze_created: bool,
+    records: Vec<RunRecord>,
 }
 
 // --- CHANGED --- The entire Simulation logic is now a dynamic loop.
 impl<'a> Simulation<'a> {
     fn new(config: &'a Config) -> Self {
+        let seed = config.resolve_seed();
+        println!("Seed: {}", seed);
         Self {
             config,
-            maze: Maze::new(config.maze_width, config.maze_height),
+            seed,
+            maze: build_maze(config, seed),
             viz: Visualization::new(config),
             maze_created: false,
+            records: Vec::new(),
         }
     }
 
-    fn run(&mut self) {
+    // Runs until the window is closed and returns one record per algorithm for export.
+    fn run(mut self) -> Vec<RunRecord> {
         while self.viz.window.is_open() && !self.viz.window.is_key_down(Key::Escape) {
             if !self.maze_created {
                 self.run_full_simulation();
@@ -483,16 +954,12 @@ impl<'a> Simulation<'a> {
             }
             self.viz.update_screen();
         }
+        self.records
     }
 
     fn run_full_simulation(&mut self) {
-        // Step 1: Generate the maze
-        if self.config.use_perfect_maze {
-            self.maze.generate_iterative();
-        } else {
-            self.maze.generate_with_loops();
-        }
-        
+        // Step 1: Show the maze generated in `Simulation::new`
+        export_svg(self.config, self.seed, &self.maze, None);
         if !self.config.skip_visualization {
             self.viz.draw_maze(&self.maze);
             sleep(Duration::from_secs(1));
@@ -500,26 +967,23 @@ impl<'a> Simulation<'a> {
         
         // Step 2: Run all chosen algorithms and collect results
         let mut results: Vec<PathfindingResult> = Vec::new();
+        let reference = reference_path(self.config, &self.maze);
         
         for (i, algo) in self.config.algorithms_to_run.iter().enumerate() {
             let info = get_algorithm_info(*algo);
             
             // Calculation is always performed
-            let (steps, duration, path, entire_path) = (info.function)(&self.maze);
-            
-            results.push(PathfindingResult {
-                name: info.name,
-                color: info.path_color,
-                steps,
-                duration,
-                path_len: path.len(),
-            });
+            let outcome = info.pathfinder.search(&self.maze);
+            results.push(PathfindingResult::new(&info, &outcome, &self.maze, reference));
+            export_png(self.config, self.seed, &self.maze, &info, &outcome);
+            export_gif(self.config, self.seed, &self.maze, &info, &outcome);
+            export_svg(self.config, self.seed, &self.maze, Some((&info, &outcome)));
 
             // Visualization only runs if not skipped
             if !self.config.skip_visualization {
                 let title = format!("Algorithm: {}", info.name);
-                self.viz.draw_search_animation(&entire_path, info.search_color, &title);
-                self.viz.draw_path(&path, info.path_color, true);
+                self.viz.draw_search_animation(&outcome, info.visit_colors(), &title);
+                self.viz.draw_path(&outcome.path, info.path_color, true);
                 sleep(Duration::from_secs(2));
 
                 // If this is not the last algorithm to visualize, reset the view
@@ -531,29 +995,58 @@ impl<'a> Simulation<'a> {
         }
         
         // Step 3: Display the final statistics screen
-        self.display_final_stats(results);
+        self.display_final_stats(&results);
+        self.records = results.iter().map(|result| RunRecord::new(self.config, self.seed, result, 0)).collect();
     }
     
     // This is now the one and only stats screen function. It dynamically renders all results.
-    fn display_final_stats(&mut self, results: Vec<PathfindingResult>) {
-        self.viz.buffer.fill(0x00101020);
+    fn display_final_stats(&mut self, results: &[PathfindingResult]) {
+        self.viz.canvas.clear();
         
         let mut y_offset = 10;
         self.viz.draw_text(10, y_offset, "--- Pathfinding Results ---", 0xFFFFFFFF);
         y_offset += 15;
 
-        let maze_type_text = format!("Maze Type:       {}", if self.config.use_perfect_maze { "Perfect (No Loops)" } else { "Imperfect (With Loops)" });
+        let maze_type_text = format!("Maze Type:       {}", self.config.maze_type_label());
         let maze_dim_text = format!("Maze Dimensions: {}x{}", self.config.maze_width, self.config.maze_height);
+        let seed_text = format!("Seed:            {}", self.config.seed_label(self.seed));
         self.viz.draw_text(10, y_offset, &maze_type_text, 0xFF808080);
         y_offset += 10;
+        if let Some(generator) = self.config.generator_label() {
+            self.viz.draw_text(10, y_offset, &format!("Generator:       {}", generator), 0xFF808080);
+            y_offset += 10;
+        }
+        self.viz.draw_text(10, y_offset, &seed_text, 0xFF808080);
+        y_offset += 10;
         self.viz.draw_text(10, y_offset, &maze_dim_text, 0xFF808080);
         y_offset += 25;
 
         for result in results {
             let stats1 = format!("Algorithm:      {}", result.name);
             let stats2 = format!("Steps Taken:    {}", result.steps);
-            let stats3 = format!("Time Elapsed:   {} ms", result.duration);
-            let stats4 = format!("Final Path Len: {}", result.path_len);
+            let stats3 = format!("Time Elapsed:   {:.3} ms", result.duration.as_secs_f64() * 1000.0);
+            // The comparison goes on the line it is about: length, or cost on terrain.
+            let excess_text = |excess: f64, reference: Reference| format!(" ({:+.1}% vs {})", excess, reference.name());
+            let (len_excess, cost_excess) = match (result.excess(), result.reference) {
+                (Some(excess), Some(reference)) if reference.weighted => (String::new(), excess_text(excess, reference)),
+                (Some(excess), Some(reference)) => (excess_text(excess, reference), String::new()),
+                _ => (String::new(), String::new()),
+            };
+            let stats4 = if result.found {
+                format!("Final Path Len: {}{}", result.path_len, len_excess)
+            } else if result.looped {
+                "Final Path Len: none, stuck in a loop".to_string()
+            } else if result.gave_up {
+                "Final Path Len: none, gave up at the expansion budget".to_string()
+            } else {
+                "Final Path Len: no path found".to_string()
+            };
+            let stats5 = format!("Frontier Peak:  {}", result.frontier_peak);
+            // Only searches without a closed set, e.g. iterative deepening, expand a cell twice.
+            let reexpanded_text = (result.reexpanded > 0).then(|| format!("Re-expanded:    {}", result.reexpanded));
+            // Hop count and cost only differ once there is terrain, e.g. BFS takes a short but
+            // expensive route where Dijkstra goes around the water.
+            let cost_text = (self.maze.is_weighted() && result.found).then(|| format!("Path Cost:      {}{}", result.path_cost, cost_excess));
             
             self.viz.draw_text(10, y_offset, &stats1, result.color);
             y_offset += 10;
@@ -562,6 +1055,16 @@ impl<'a> Simulation<'a> {
             self.viz.draw_text(10, y_offset, &stats3, 0xFFFFFFFF);
             y_offset += 10;
             self.viz.draw_text(10, y_offset, &stats4, 0xFFFFFFFF);
+            y_offset += 10;
+            self.viz.draw_text(10, y_offset, &stats5, 0xFFFFFFFF);
+            if let Some(reexpanded_text) = reexpanded_text {
+                y_offset += 10;
+                self.viz.draw_text(10, y_offset, &reexpanded_text, 0xFFFFFFFF);
+            }
+            if let Some(cost_text) = cost_text {
+                y_offset += 10;
+                self.viz.draw_text(10, y_offset, &cost_text, 0xFFFFFFFF);
+            }
             y_offset += 40; // Add spacing for the next algorithm
         }
     }
@@ -569,16 +1072,30 @@ impl<'a> Simulation<'a> {
 
 
 fn main() {
-    // --- CHANGED --- This is now the single point of control.
-    // Simply edit the vector to change which algorithms are run and in what order.
-    let config = Config {
-        // examples:
-        // algorithms_to_run: vec![Algorithm::Bfs],
-        // algorithms_to_run: vec![Algorithm::Dfs, Algorithm::Bfs],
-        algorithms_to_run: vec![Algorithm::Bfs, Algorithm::Dfs],
-        ..Default::default()
-    };
+    // Every `Config` field can be set from the command line, e.g.
+    // `cargo run -- --width 400 --height 300 --algo bfs,astar --perfect --seed 42 --headless`.
+    // Anything not given falls back to `Config::default()`. `--experiment file.toml` expands
+    // into one `Config` per maze listed in the file, run one after another.
+    let (configs, export_targets) = Cli::parse_configs();
+
+    let mut records = Vec::new();
+    for config in &configs {
+        if let Some(plan) = config.benchmark {
+            records.extend(benchmark::run(config, plan));
+            println!();
+        } else if let Some(plan) = config.dynamic {
+            dynamic::run(config, plan);
+            println!();
+        } else if config.headless {
+            records.extend(headless::run(config));
+            println!();
+        } else {
+            records.extend(Simulation::new(config).run());
+        }
+    }
 
-    let mut simulation = Simulation::new(&config);
-    simulation.run();
-}
\ No newline at end of file
+    if let Err(message) = export_targets.write(&records) {
+        eprintln!("error: failed to export results: {}", message);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,1249 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::bitset::BitSet;
+use crate::{Hand, Heuristic, Maze};
+
+// Everything a search reports back. Named fields instead of the old anonymous tuple,
+// so adding a metric doesn't silently shift the others around.
+pub struct SearchOutcome {
+    // Number of cells taken off the frontier and expanded.
+    pub expanded: usize,
+    // How many of those expansions (or, for walkers, moves) were of a cell already expanded
+    // before. Zero for searches with a closed set; iterative deepening repeats whole layers.
+    pub reexpanded: usize,
+    // Largest size the frontier (queue, stack or open list) reached during the search.
+    pub frontier_peak: usize,
+    pub found: bool,
+    // Start-to-end path, empty when the end point was not reached.
+    pub path: Vec<(usize, usize)>,
+    // Every visit event in the order it happened, used by the search animation.
+    pub visits: Vec<Visit>,
+    // Set when a memoryless walker (see `WallFollower`) gave up because it was going in circles.
+    pub looped: bool,
+    // Set when a search stopped at its expansion budget before it found the end or ruled it
+    // out, as iterative deepening does when lost among the paths around loops.
+    pub gave_up: bool,
+    pub elapsed: Duration,
+}
+
+// What happened to a cell in a visit event, which decides how the animation draws it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VisitKind {
+    // Reached by the search from the start point.
+    Discovered,
+    // Reached by the frontier grown from the end point (see `BidirectionalBfs`).
+    DiscoveredFromEnd,
+    // Ruled out and sealed off, as in `DeadEndFilling`.
+    Filled,
+    // Put on the open list by `JumpPointSearch`; the cells it jumps over are `Discovered`.
+    JumpPoint,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Visit {
+    pub cell: (usize, usize),
+    pub kind: VisitKind,
+}
+
+impl Visit {
+    fn discovered(cell: (usize, usize)) -> Self {
+        Self { cell, kind: VisitKind::Discovered }
+    }
+}
+
+// A search strategy. Implementors are plain structs, so parameters such as the
+// heuristic travel with the algorithm instead of being baked into a fn pointer.
+pub trait Pathfinder {
+    fn search(&self, maze: &Maze) -> SearchOutcome;
+}
+
+// Walks `came_from` back from the end point. Returns an empty path when the end was never reached.
+fn reconstruct_path(maze: &Maze, came_from: &[Option<(usize, usize)>]) -> Vec<(usize, usize)> {
+    if came_from[maze.index(maze.end_point)].is_none() {
+        return Vec::new();
+    }
+
+    let mut path = Vec::new();
+    let mut current = maze.end_point;
+    while current != maze.start_point {
+        path.push(current);
+        match came_from[maze.index(current)] {
+            Some(prev) if prev != current => current = prev,
+            _ => break,
+        }
+    }
+    path.push(maze.start_point);
+    path.reverse();
+    path
+}
+
+pub struct Bfs;
+
+impl Pathfinder for Bfs {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; maze.width * maze.height];
+        let mut queue = VecDeque::new();
+        let mut visits = Vec::new();
+
+        queue.push_back(maze.start_point);
+        came_from[maze.index(maze.start_point)] = Some(maze.start_point);
+        visits.push(Visit::discovered(maze.start_point));
+
+        let mut expanded = 0;
+        let mut frontier_peak = queue.len();
+
+        while let Some(current) = queue.pop_front() {
+            expanded += 1;
+            if current == maze.end_point {
+                break;
+            }
+            for (_, next) in maze.open_neighbors(current) {
+                if came_from[maze.index(next)].is_none() {
+                    came_from[maze.index(next)] = Some(current);
+                    queue.push_back(next);
+                    visits.push(Visit::discovered(next));
+                }
+            }
+            frontier_peak = frontier_peak.max(queue.len());
+        }
+
+        let path = reconstruct_path(maze, &came_from);
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// Follows `came_from` links from `position` until reaching the cell that is its own parent.
+fn walk_back(maze: &Maze, came_from: &[Option<(usize, usize)>], mut position: (usize, usize)) -> Vec<(usize, usize)> {
+    let mut cells = vec![position];
+    while let Some(prev) = came_from[maze.index(position)].filter(|&prev| prev != position) {
+        cells.push(prev);
+        position = prev;
+    }
+    cells
+}
+
+// BFS from both ends at once. The two sides take turns expanding a whole layer; once a layer
+// touches the other side, the shortest connection found in that layer is the shortest path.
+// Each side only has to reach about half the distance, so far fewer cells are explored.
+pub struct BidirectionalBfs;
+
+impl Pathfinder for BidirectionalBfs {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let cells = maze.width * maze.height;
+        // Index 0 grows from the start point, index 1 from the end point.
+        let mut came_from: [Vec<Option<(usize, usize)>>; 2] = [vec![None; cells], vec![None; cells]];
+        let mut depth: [Vec<usize>; 2] = [vec![usize::MAX; cells], vec![usize::MAX; cells]];
+        let mut queues = [VecDeque::new(), VecDeque::new()];
+        let mut visits = Vec::new();
+        const KINDS: [VisitKind; 2] = [VisitKind::Discovered, VisitKind::DiscoveredFromEnd];
+
+        for (side, root) in [maze.start_point, maze.end_point].into_iter().enumerate() {
+            came_from[side][maze.index(root)] = Some(root);
+            depth[side][maze.index(root)] = 0;
+            queues[side].push_back(root);
+            if side == 0 || root != maze.start_point {
+                visits.push(Visit { cell: root, kind: KINDS[side] });
+            }
+        }
+
+        let mut expanded = 0;
+        let mut frontier_peak = 2;
+        // Shortest connection as (length, cell on the start side, cell on the end side).
+        let mut meeting = (maze.start_point == maze.end_point).then_some((0, maze.start_point, maze.end_point));
+        let mut side = 0;
+
+        while meeting.is_none() && !queues[0].is_empty() && !queues[1].is_empty() {
+            let other = 1 - side;
+            for _ in 0..queues[side].len() {
+                let current = queues[side].pop_front().unwrap();
+                let current_depth = depth[side][maze.index(current)];
+                expanded += 1;
+                for (_, next) in maze.open_neighbors(current) {
+                    let next_idx = maze.index(next);
+                    if depth[other][next_idx] != usize::MAX {
+                        let length = current_depth + 1 + depth[other][next_idx];
+                        let ends = if side == 0 { (current, next) } else { (next, current) };
+                        if meeting.is_none_or(|(best, _, _)| length < best) {
+                            meeting = Some((length, ends.0, ends.1));
+                        }
+                    }
+                    if depth[side][next_idx] == usize::MAX {
+                        depth[side][next_idx] = current_depth + 1;
+                        came_from[side][next_idx] = Some(current);
+                        queues[side].push_back(next);
+                        visits.push(Visit { cell: next, kind: KINDS[side] });
+                    }
+                }
+            }
+            frontier_peak = frontier_peak.max(queues[0].len() + queues[1].len());
+            side = other;
+        }
+
+        let path = match meeting {
+            Some((_, start_side, end_side)) => {
+                let mut path = walk_back(maze, &came_from[0], start_side);
+                path.reverse();
+                if end_side != start_side {
+                    path.extend(walk_back(maze, &came_from[1], end_side));
+                }
+                path
+            }
+            None => Vec::new(),
+        };
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+pub struct Dfs;
+
+impl Pathfinder for Dfs {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; maze.width * maze.height];
+        let mut stack = vec![maze.start_point];
+        let mut visits = Vec::new();
+
+        came_from[maze.index(maze.start_point)] = Some(maze.start_point);
+        visits.push(Visit::discovered(maze.start_point));
+
+        let mut expanded = 0;
+        let mut frontier_peak = stack.len();
+
+        while let Some(current) = stack.pop() {
+            expanded += 1;
+            if current == maze.end_point {
+                break;
+            }
+            for (_, next) in maze.open_neighbors(current) {
+                if came_from[maze.index(next)].is_none() {
+                    came_from[maze.index(next)] = Some(current);
+                    stack.push(next);
+                    visits.push(Visit::discovered(next));
+                }
+            }
+            frontier_peak = frontier_peak.max(stack.len());
+        }
+
+        let path = reconstruct_path(maze, &came_from);
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// An open-list entry for A*. Ordered so that `BinaryHeap` pops the lowest f-score first,
+// preferring the entry with the larger g-score (closer to the goal) on ties.
+struct AStarNode {
+    f_score: f64,
+    g_score: usize,
+    position: (usize, usize),
+}
+
+impl PartialEq for AStarNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for AStarNode {}
+
+impl PartialOrd for AStarNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AStarNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .f_score
+            .total_cmp(&self.f_score)
+            .then_with(|| self.g_score.cmp(&other.g_score))
+    }
+}
+
+// A* over terrain costs: `g` is the summed cost of the cells entered so far. Every cell costs
+// at least 1, so the heuristics, which count cells, never overestimate and with a `weight` of 1
+// the path found is the cheapest one. A larger weight (f = g + w·h) trusts the estimate more:
+// fewer cells are expanded, but the path can be up to `weight` times as expensive.
+pub struct AStar {
+    pub heuristic: Heuristic,
+    pub weight: f64,
+}
+
+impl Pathfinder for AStar {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; maze.width * maze.height];
+        let mut best_g: Vec<usize> = vec![usize::MAX; maze.width * maze.height];
+        let mut closed: Vec<bool> = vec![false; maze.width * maze.height];
+        let mut open = BinaryHeap::new();
+        let mut visits = Vec::new();
+
+        let start_idx = maze.index(maze.start_point);
+        came_from[start_idx] = Some(maze.start_point);
+        best_g[start_idx] = 0;
+        visits.push(Visit::discovered(maze.start_point));
+        open.push(AStarNode {
+            f_score: self.weight * self.heuristic.estimate(maze.start_point, maze.end_point),
+            g_score: 0,
+            position: maze.start_point,
+        });
+
+        let mut expanded = 0;
+        let mut frontier_peak = open.len();
+
+        while let Some(AStarNode { g_score, position: current, .. }) = open.pop() {
+            let current_idx = maze.index(current);
+            // Stale entries are left in the heap when a cheaper route is found later.
+            if closed[current_idx] {
+                continue;
+            }
+            closed[current_idx] = true;
+            expanded += 1;
+            if current == maze.end_point {
+                break;
+            }
+
+            for (_, next) in maze.open_neighbors(current) {
+                let next_idx = maze.index(next);
+                let tentative_g = g_score + maze.cost(next);
+                if tentative_g < best_g[next_idx] {
+                    if came_from[next_idx].is_none() {
+                        visits.push(Visit::discovered(next));
+                    }
+                    best_g[next_idx] = tentative_g;
+                    came_from[next_idx] = Some(current);
+                    open.push(AStarNode {
+                        f_score: tentative_g as f64 + self.weight * self.heuristic.estimate(next, maze.end_point),
+                        g_score: tentative_g,
+                        position: next,
+                    });
+                }
+            }
+            frontier_peak = frontier_peak.max(open.len());
+        }
+
+        let path = reconstruct_path(maze, &came_from);
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// Greedy best-first search: always expands the open cell that looks closest to the end point,
+// ignoring how far it is from the start. Usually the fastest to reach the end, but the path
+// is whatever the first route there happened to be, with no optimality guarantee.
+pub struct GreedyBestFirst {
+    pub heuristic: Heuristic,
+}
+
+impl Pathfinder for GreedyBestFirst {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; maze.width * maze.height];
+        let mut open = BinaryHeap::new();
+        let mut visits = Vec::new();
+
+        came_from[maze.index(maze.start_point)] = Some(maze.start_point);
+        visits.push(Visit::discovered(maze.start_point));
+        // The f-score is the estimate alone; the g-score only breaks ties towards deeper cells.
+        open.push(AStarNode {
+            f_score: self.heuristic.estimate(maze.start_point, maze.end_point),
+            g_score: 0,
+            position: maze.start_point,
+        });
+
+        let mut expanded = 0;
+        let mut frontier_peak = open.len();
+
+        while let Some(AStarNode { g_score, position: current, .. }) = open.pop() {
+            expanded += 1;
+            if current == maze.end_point {
+                break;
+            }
+            for (_, next) in maze.open_neighbors(current) {
+                if came_from[maze.index(next)].is_none() {
+                    came_from[maze.index(next)] = Some(current);
+                    visits.push(Visit::discovered(next));
+                    open.push(AStarNode {
+                        f_score: self.heuristic.estimate(next, maze.end_point),
+                        g_score: g_score + 1,
+                        position: next,
+                    });
+                }
+            }
+            frontier_peak = frontier_peak.max(open.len());
+        }
+
+        let path = reconstruct_path(maze, &came_from);
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// A Dijkstra open-list entry, ordered so that `BinaryHeap` pops the lowest cost first.
+#[derive(PartialEq, Eq)]
+struct DijkstraNode {
+    cost: usize,
+    position: (usize, usize),
+}
+
+impl PartialOrd for DijkstraNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DijkstraNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost).then_with(|| self.position.cmp(&other.position))
+    }
+}
+
+// Dijkstra's algorithm: always expands the cheapest cell found so far, so the first time the
+// end point is expanded its path is the cheapest one under the terrain costs. Explores in
+// rings of equal cost rather than heading for the goal.
+pub struct Dijkstra;
+
+impl Pathfinder for Dijkstra {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; maze.width * maze.height];
+        let mut best_cost: Vec<usize> = vec![usize::MAX; maze.width * maze.height];
+        let mut open = BinaryHeap::new();
+        let mut visits = Vec::new();
+
+        let start_idx = maze.index(maze.start_point);
+        came_from[start_idx] = Some(maze.start_point);
+        best_cost[start_idx] = 0;
+        visits.push(Visit::discovered(maze.start_point));
+        open.push(DijkstraNode { cost: 0, position: maze.start_point });
+
+        let mut expanded = 0;
+        let mut frontier_peak = open.len();
+
+        while let Some(DijkstraNode { cost, position: current }) = open.pop() {
+            // Entries superseded by a cheaper route are skipped instead of removed from the heap.
+            if cost > best_cost[maze.index(current)] {
+                continue;
+            }
+            expanded += 1;
+            if current == maze.end_point {
+                break;
+            }
+
+            for (_, next) in maze.open_neighbors(current) {
+                let next_idx = maze.index(next);
+                let next_cost = cost + maze.cost(next);
+                if next_cost < best_cost[next_idx] {
+                    if came_from[next_idx].is_none() {
+                        visits.push(Visit::discovered(next));
+                    }
+                    best_cost[next_idx] = next_cost;
+                    came_from[next_idx] = Some(current);
+                    open.push(DijkstraNode { cost: next_cost, position: next });
+                }
+            }
+            frontier_peak = frontier_peak.max(open.len());
+        }
+
+        let path = reconstruct_path(maze, &came_from);
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// The direction (0 = up, 1 = right, 2 = down, 3 = left) that points most directly from `from`
+// towards `to`, preferring the horizontal on ties.
+fn heading_towards(from: (usize, usize), to: (usize, usize)) -> usize {
+    if from.0.abs_diff(to.0) >= from.1.abs_diff(to.1) {
+        if to.0 >= from.0 { 1 } else { 3 }
+    } else if to.1 > from.1 {
+        2
+    } else {
+        0
+    }
+}
+
+// A walker that only sees the walls around it: every step and every new cell is recorded.
+// The path it reports is the whole walk, dead ends and all, because that is the distance a
+// person following the rule would actually cover.
+pub struct Walk {
+    pub position: (usize, usize),
+    path: Vec<(usize, usize)>,
+    visits: Vec<Visit>,
+    seen: BitSet,
+}
+
+impl Walk {
+    pub fn new(maze: &Maze) -> Self {
+        let mut seen = BitSet::new(maze.width * maze.height, false);
+        seen.set(maze.index(maze.start_point), true);
+        Self { position: maze.start_point, path: vec![maze.start_point], visits: vec![Visit::discovered(maze.start_point)], seen }
+    }
+
+    pub fn step(&mut self, maze: &Maze, dir: usize) {
+        self.position = maze.neighbor(self.position, dir).expect("walkers only step through open walls");
+        self.path.push(self.position);
+        if !self.seen.get(maze.index(self.position)) {
+            self.seen.set(maze.index(self.position), true);
+            self.visits.push(Visit::discovered(self.position));
+        }
+    }
+
+    pub fn has_entered(&self, maze: &Maze, position: (usize, usize)) -> bool {
+        self.seen.get(maze.index(position))
+    }
+
+    pub fn finish(self, maze: &Maze, looped: bool, start_time: Instant) -> SearchOutcome {
+        let found = self.position == maze.end_point;
+        SearchOutcome {
+            expanded: self.path.len() - 1,
+            // Every move into a cell entered before.
+            reexpanded: self.path.len() - self.visits.len(),
+            // The walker carries no frontier, only its own position.
+            frontier_peak: 1,
+            found,
+            path: if found { self.path } else { Vec::new() },
+            visits: self.visits,
+            looped,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// The turn a wall follower takes: the first open direction out of "towards its hand",
+// straight on, away from its hand, and back, as quarter turns clockwise.
+fn follow_wall(maze: &Maze, position: (usize, usize), heading: usize, hand: Hand) -> Option<usize> {
+    let turns: [usize; 4] = match hand {
+        Hand::Left => [3, 0, 1, 2],
+        Hand::Right => [1, 0, 3, 2],
+    };
+    turns.into_iter().find(|&turn| !maze.has_wall(position, (heading + turn) % 4))
+}
+
+// Keeps one hand on the wall. Always escapes a perfect maze, but when the end point sits on
+// a wall that is not connected to the start's (an island, which loops create), it walks the
+// same circuit forever. The walk is deterministic, so coming back to a cell with the same
+// heading proves it is looping and the search gives up.
+pub struct WallFollower {
+    pub hand: Hand,
+}
+
+impl Pathfinder for WallFollower {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut walk = Walk::new(maze);
+        let mut heading = heading_towards(maze.start_point, maze.end_point);
+        // One bit per (cell, heading) state the walker has left from.
+        let mut states = BitSet::new(4 * maze.width * maze.height, false);
+
+        while walk.position != maze.end_point {
+            let state = 4 * maze.index(walk.position) + heading;
+            if states.get(state) {
+                return walk.finish(maze, true, start_time);
+            }
+            states.set(state, true);
+            let Some(turn) = follow_wall(maze, walk.position, heading, self.hand) else { break };
+            heading = (heading + turn) % 4;
+            walk.step(maze, heading);
+        }
+        walk.finish(maze, false, start_time)
+    }
+}
+
+// A Pledge walker gives up after this many steps per cell. Its turn counter can grow without
+// bound while it circles an island, so repeated states alone do not always catch the loop.
+const PLEDGE_STEPS_PER_CELL: usize = 16;
+
+// The Pledge algorithm: head in a fixed preferred direction (towards the end point at the
+// start); on hitting a wall, follow it with the left hand while counting quarter turns, and
+// let go once the count is back to zero. The counter lets it leave islands that trap a plain
+// wall follower when escaping a maze, but an end point inside the maze can still be missed,
+// in which case the walker loops and the search gives up.
+pub struct Pledge;
+
+impl Pathfinder for Pledge {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let mut walk = Walk::new(maze);
+        let preferred = heading_towards(maze.start_point, maze.end_point);
+        let mut heading = preferred;
+        // Net clockwise quarter turns while following a wall; `None` when walking freely.
+        let mut turns: Option<i64> = None;
+        let mut states = HashSet::new();
+        let step_limit = PLEDGE_STEPS_PER_CELL * maze.width * maze.height;
+
+        while walk.position != maze.end_point {
+            if !states.insert((walk.position, heading, turns)) || walk.path.len() > step_limit {
+                return walk.finish(maze, true, start_time);
+            }
+            match turns {
+                None if !maze.has_wall(walk.position, preferred) => walk.step(maze, preferred),
+                None => {
+                    // Turn right until the way is clear, keeping the obstacle on the left.
+                    let mut count = 0;
+                    while maze.has_wall(walk.position, heading) {
+                        if count == 4 {
+                            return walk.finish(maze, false, start_time);
+                        }
+                        heading = (heading + 1) % 4;
+                        count += 1;
+                    }
+                    turns = Some(count);
+                    walk.step(maze, heading);
+                }
+                Some(0) => turns = None,
+                Some(count) => {
+                    let Some(turn) = follow_wall(maze, walk.position, heading, Hand::Left) else { break };
+                    heading = (heading + turn) % 4;
+                    // Quarter turns clockwise as a signed count: left is -1, back is +2.
+                    turns = Some(count + [0, 1, 2, -1][turn]);
+                    walk.step(maze, heading);
+                }
+            }
+        }
+        walk.finish(maze, false, start_time)
+    }
+}
+
+// Trémaux's algorithm: walk the maze marking each passage every time it is walked. Take an
+// unmarked passage when there is one; on entering an already visited cell through a fresh
+// passage, turn straight back; otherwise take the least marked passage, never one marked
+// twice. Passages marked once always form a route from the start to the walker, so that is
+// the path reported once it reaches the end. Cells left with every passage marked twice can
+// never be entered again and are shown as filled.
+pub struct Tremaux;
+
+impl Pathfinder for Tremaux {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        // Times each passage has been walked, indexed like the wall bits.
+        let mut marks = vec![0u8; 2 * maze.width * maze.height];
+        let passage = |position, dir| maze.wall_bit(position, dir).expect("open walls are never on the border");
+        let open = |position| (0..4).filter(move |&dir| !maze.has_wall(position, dir));
+        let mut seen = BitSet::new(maze.width * maze.height, false);
+        seen.set(maze.index(maze.start_point), true);
+        let mut visits = vec![Visit::discovered(maze.start_point)];
+
+        let mut position = maze.start_point;
+        // Direction of the last move, and whether it led into a cell visited before.
+        let mut arrived_by = None;
+        let mut revisit = false;
+        let mut moves = 0;
+
+        while position != maze.end_point {
+            let back = arrived_by.map(|dir: usize| (dir + 2) % 4);
+            let dir = match back {
+                Some(back) if revisit && marks[passage(position, back)] == 1 => Some(back),
+                _ => open(position)
+                    .find(|&dir| Some(dir) != back && marks[passage(position, dir)] == 0)
+                    .or_else(|| {
+                        open(position)
+                            .filter(|&dir| marks[passage(position, dir)] < 2)
+                            .min_by_key(|&dir| marks[passage(position, dir)])
+                    }),
+            };
+            // Every passage is marked twice: the walker is back at the start with nowhere to go.
+            let Some(dir) = dir else { break };
+
+            marks[passage(position, dir)] += 1;
+            moves += 1;
+            let left = position;
+            position = maze.neighbor(position, dir).expect("walkers only step through open walls");
+            if open(left).all(|dir| marks[passage(left, dir)] == 2) {
+                visits.push(Visit { cell: left, kind: VisitKind::Filled });
+            }
+            revisit = seen.get(maze.index(position));
+            if !revisit {
+                seen.set(maze.index(position), true);
+                visits.push(Visit::discovered(position));
+            }
+            arrived_by = Some(dir);
+        }
+
+        let found = position == maze.end_point;
+        let mut path = Vec::new();
+        if found {
+            let (mut previous, mut cell) = (None, maze.start_point);
+            path.push(cell);
+            while cell != maze.end_point {
+                let next = open(cell)
+                    .filter(|&dir| marks[passage(cell, dir)] == 1)
+                    .filter_map(|dir| maze.neighbor(cell, dir))
+                    .find(|&next| Some(next) != previous);
+                let Some(next) = next else { break };
+                previous = Some(cell);
+                cell = next;
+                path.push(cell);
+            }
+        }
+        SearchOutcome {
+            expanded: moves,
+            reexpanded: moves + 1 - visits.iter().filter(|visit| visit.kind == VisitKind::Discovered).count(),
+            // Like the wall followers it carries no frontier, only the marks it leaves behind.
+            frontier_peak: 1,
+            found,
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// Dead-end filling: seal every dead end (a cell with three walls, counting sealed neighbours
+// as walls) other than the start and end, and keep going as sealing exposes new ones. In a
+// perfect maze only the solution survives. With loops, whole loops can survive too, and the
+// path is whatever a depth-first walk through the remaining cells finds, so it need not be
+// the shortest.
+pub struct DeadEndFilling;
+
+impl Pathfinder for DeadEndFilling {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let cells = maze.width * maze.height;
+        let is_endpoint = |position| position == maze.start_point || position == maze.end_point;
+        // Open passages to cells that are not sealed yet.
+        let mut exits: Vec<u8> = vec![0; cells];
+        let mut queue = VecDeque::new();
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                exits[maze.index((x, y))] = maze.open_neighbors((x, y)).count() as u8;
+                // Cells without any exit are solid already, there is nothing to fill.
+                if exits[maze.index((x, y))] == 1 && !is_endpoint((x, y)) {
+                    queue.push_back((x, y));
+                }
+            }
+        }
+
+        let mut sealed = BitSet::new(cells, false);
+        let mut visits = Vec::new();
+        let mut expanded = 0;
+        let mut frontier_peak = queue.len();
+        while let Some(current) = queue.pop_front() {
+            sealed.set(maze.index(current), true);
+            visits.push(Visit { cell: current, kind: VisitKind::Filled });
+            expanded += 1;
+            for (_, next) in maze.open_neighbors(current) {
+                let next_idx = maze.index(next);
+                if sealed.get(next_idx) {
+                    continue;
+                }
+                exits[next_idx] -= 1;
+                if exits[next_idx] == 1 && !is_endpoint(next) {
+                    queue.push_back(next);
+                }
+            }
+            frontier_peak = frontier_peak.max(queue.len());
+        }
+
+        // Walk what is left from the start.
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; cells];
+        let mut stack = vec![maze.start_point];
+        came_from[maze.index(maze.start_point)] = Some(maze.start_point);
+        while let Some(current) = stack.pop() {
+            expanded += 1;
+            if current == maze.end_point {
+                break;
+            }
+            for (_, next) in maze.open_neighbors(current) {
+                if !sealed.get(maze.index(next)) && came_from[maze.index(next)].is_none() {
+                    came_from[maze.index(next)] = Some(current);
+                    stack.push(next);
+                }
+            }
+            frontier_peak = frontier_peak.max(stack.len());
+        }
+
+        let path = reconstruct_path(maze, &came_from);
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// An iterative deepening pass gives up after expanding this many times the number of cells.
+// In a perfect maze a pass expands every cell at most once, since there is only one path to
+// it; with loops the number of paths within the bound can grow exponentially.
+const DEEPENING_PASS_EXPANSIONS_PER_CELL: usize = 16;
+
+// The result of one bounded depth-first pass of `IterativeDeepening`.
+enum Pass {
+    Found(Vec<(usize, usize)>),
+    // The end was not within the bound; the smallest f-value that was cut off is the next bound.
+    Exceeded(f64),
+    // Nothing was cut off, so a larger bound would not reach any more cells.
+    Exhausted,
+    // The pass went over its expansion budget.
+    GaveUp,
+}
+
+// Shared machinery for IDDFS and IDA*: repeated depth-first passes, each admitting cells whose
+// f = g + h fits under a bound that grows between passes. Only the current path is kept (with
+// a bit per cell to avoid walking in circles along it), so memory is bounded by the longest
+// path rather than the number of cells. The price is that every pass starts over, and with
+// loops a cell can be reached along many different paths within one pass, so a pass that
+// runs over `DEEPENING_PASS_EXPANSIONS_PER_CELL` gives up and reports that in `gave_up`.
+struct IterativeDeepening<'a> {
+    maze: &'a Maze,
+    cost: &'a dyn Fn((usize, usize)) -> usize,
+    estimate: &'a dyn Fn((usize, usize)) -> f64,
+    expanded: usize,
+    stack_peak: usize,
+    // Cells expanded in any pass, for the animation and the re-expansion count.
+    seen: BitSet,
+    visits: Vec<Visit>,
+}
+
+impl<'a> IterativeDeepening<'a> {
+    fn new(maze: &'a Maze, cost: &'a dyn Fn((usize, usize)) -> usize, estimate: &'a dyn Fn((usize, usize)) -> f64) -> Self {
+        let seen = BitSet::new(maze.width * maze.height, false);
+        Self { maze, cost, estimate, expanded: 0, stack_peak: 0, seen, visits: Vec::new() }
+    }
+
+    fn expand(&mut self, position: (usize, usize)) {
+        self.expanded += 1;
+        if !self.seen.get(self.maze.index(position)) {
+            self.seen.set(self.maze.index(position), true);
+            self.visits.push(Visit::discovered(position));
+        }
+    }
+
+    // One depth-first pass admitting cells with f <= `bound`.
+    fn pass(&mut self, bound: f64) -> Pass {
+        let maze = self.maze;
+        let mut on_path = BitSet::new(maze.width * maze.height, false);
+        // (cell, g, next direction to try) for every cell on the current path.
+        let mut stack = vec![(maze.start_point, 0, 0)];
+        on_path.set(maze.index(maze.start_point), true);
+        self.expand(maze.start_point);
+        self.stack_peak = self.stack_peak.max(stack.len());
+        if maze.start_point == maze.end_point {
+            return Pass::Found(vec![maze.start_point]);
+        }
+
+        let budget = self.expanded + DEEPENING_PASS_EXPANSIONS_PER_CELL * maze.width * maze.height;
+        let mut next_bound = f64::INFINITY;
+        while let Some((position, g, dir)) = stack.last_mut() {
+            let (position, g) = (*position, *g);
+            if *dir == 4 {
+                stack.pop();
+                on_path.set(maze.index(position), false);
+                continue;
+            }
+            let current_dir = *dir;
+            *dir += 1;
+            let Some(next) = maze.neighbor(position, current_dir).filter(|_| !maze.has_wall(position, current_dir)) else {
+                continue;
+            };
+            if on_path.get(maze.index(next)) {
+                continue;
+            }
+            let next_g = g + (self.cost)(next);
+            let f = next_g as f64 + (self.estimate)(next);
+            if f > bound {
+                next_bound = next_bound.min(f);
+                continue;
+            }
+            if self.expanded >= budget {
+                return Pass::GaveUp;
+            }
+            self.expand(next);
+            stack.push((next, next_g, 0));
+            on_path.set(maze.index(next), true);
+            self.stack_peak = self.stack_peak.max(stack.len());
+            if next == maze.end_point {
+                return Pass::Found(stack.iter().map(|&(cell, _, _)| cell).collect());
+            }
+        }
+        if next_bound.is_finite() { Pass::Exceeded(next_bound) } else { Pass::Exhausted }
+    }
+
+    fn run(mut self, start_time: Instant) -> SearchOutcome {
+        let mut bound = (self.estimate)(self.maze.start_point);
+        let (path, gave_up) = loop {
+            match self.pass(bound) {
+                Pass::Found(path) => break (path, false),
+                Pass::Exceeded(next_bound) => bound = next_bound,
+                Pass::Exhausted => break (Vec::new(), false),
+                Pass::GaveUp => break (Vec::new(), true),
+            }
+        };
+        SearchOutcome {
+            expanded: self.expanded,
+            reexpanded: self.expanded - self.visits.len(),
+            // The stack holds the current path and nothing else, so this is the peak memory.
+            frontier_peak: self.stack_peak,
+            found: !path.is_empty(),
+            path,
+            visits: self.visits,
+            looped: false,
+            gave_up,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+// Iterative deepening DFS: depth-limited DFS with the limit raised by one cell per pass. Finds
+// the path with the fewest cells, like BFS, while only ever holding one path in memory.
+pub struct Iddfs;
+
+impl Pathfinder for Iddfs {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        IterativeDeepening::new(maze, &|_| 1, &|_| 0.0).run(start_time)
+    }
+}
+
+// IDA*: iterative deepening on f = g + h over terrain costs, each pass raising the bound to the
+// smallest f that was cut off. Finds the cheapest path, like A*, without an open list.
+pub struct IdaStar {
+    pub heuristic: Heuristic,
+}
+
+impl Pathfinder for IdaStar {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let estimate = |position| self.heuristic.estimate(position, maze.end_point);
+        IterativeDeepening::new(maze, &|position| maze.cost(position), &estimate).run(start_time)
+    }
+}
+
+// Jump Point Search on the 4-connected cell grid (the JPS4 variant): A* that only queues "jump
+// points" and skips over straight runs in one jump. Among equally short paths it only follows
+// canonical ones, which move horizontally before turning vertically, so a horizontal run also
+// tries vertical jumps at every cell and stops where one finds something, while a vertical run
+// only stops at a forced neighbour: a side passage that no horizontal-first path could reach.
+// Counts cells like BFS, ignoring terrain. `expanded` counts jump points, not scanned cells.
+pub struct JumpPointSearch;
+
+impl JumpPointSearch {
+    // Whether `side` out of `position`, entered vertically from `previous`, is forced: it is open,
+    // but the horizontal-first route through `previous` to the same cell is walled off.
+    fn forced(maze: &Maze, previous: (usize, usize), position: (usize, usize), side: usize) -> bool {
+        if maze.has_wall(position, side) {
+            return false;
+        }
+        let vertical = heading_towards(previous, position);
+        maze.has_wall(previous, side) || maze.neighbor(previous, side).is_none_or(|beside| maze.has_wall(beside, vertical))
+    }
+
+    // Moves from `position` in `dir` until reaching a jump point, returning it with the number of
+    // cells moved, or `None` when the run ends in a wall without finding one.
+    fn jump(&self, maze: &Maze, mut position: (usize, usize), dir: usize, scan: &mut Scan) -> Option<((usize, usize), usize)> {
+        let mut distance = 0;
+        while !maze.has_wall(position, dir) {
+            let previous = position;
+            position = maze.neighbor(position, dir).expect("open walls are never on the border");
+            distance += 1;
+            scan.discover(maze, position);
+            let stop = position == maze.end_point
+                || if dir % 2 == 1 {
+                    [0, 2].into_iter().any(|vertical| self.jump(maze, position, vertical, scan).is_some())
+                } else {
+                    [1, 3].into_iter().any(|side| Self::forced(maze, previous, position, side))
+                };
+            if stop {
+                return Some((position, distance));
+            }
+        }
+        None
+    }
+
+    // The directions worth jumping in from a jump point entered moving `arrived`.
+    fn successors(maze: &Maze, position: (usize, usize), arrived: Option<usize>) -> Vec<usize> {
+        match arrived {
+            None => vec![0, 1, 2, 3],
+            Some(dir) if dir % 2 == 1 => vec![dir, 0, 2],
+            Some(dir) => {
+                let previous = maze.neighbor(position, (dir + 2) % 4).expect("entered from this side");
+                let forced = [1, 3].into_iter().filter(|&side| Self::forced(maze, previous, position, side));
+                std::iter::once(dir).chain(forced).collect()
+            }
+        }
+    }
+}
+
+// Cells scanned by `JumpPointSearch`, for the animation.
+struct Scan {
+    seen: BitSet,
+    visits: Vec<Visit>,
+}
+
+impl Scan {
+    fn discover(&mut self, maze: &Maze, position: (usize, usize)) {
+        if !self.seen.get(maze.index(position)) {
+            self.seen.set(maze.index(position), true);
+            self.visits.push(Visit::discovered(position));
+        }
+    }
+}
+
+impl Pathfinder for JumpPointSearch {
+    fn search(&self, maze: &Maze) -> SearchOutcome {
+        let start_time = Instant::now();
+        let cells = maze.width * maze.height;
+        let mut came_from: Vec<Option<(usize, usize)>> = vec![None; cells];
+        // Direction of the jump that gave each jump point its best g-score.
+        let mut arrived: Vec<Option<usize>> = vec![None; cells];
+        let mut best_g: Vec<usize> = vec![usize::MAX; cells];
+        let mut closed = BitSet::new(cells, false);
+        let mut scan = Scan { seen: BitSet::new(cells, false), visits: Vec::new() };
+        let mut open = BinaryHeap::new();
+        let estimate = |position| Heuristic::Manhattan.estimate(position, maze.end_point);
+
+        let start_idx = maze.index(maze.start_point);
+        came_from[start_idx] = Some(maze.start_point);
+        best_g[start_idx] = 0;
+        scan.discover(maze, maze.start_point);
+        open.push(AStarNode { f_score: estimate(maze.start_point), g_score: 0, position: maze.start_point });
+
+        let mut expanded = 0;
+        let mut frontier_peak = open.len();
+
+        while let Some(AStarNode { g_score, position: current, .. }) = open.pop() {
+            let current_idx = maze.index(current);
+            if closed.get(current_idx) {
+                continue;
+            }
+            closed.set(current_idx, true);
+            expanded += 1;
+            if current == maze.end_point {
+                break;
+            }
+
+            for dir in Self::successors(maze, current, arrived[current_idx]) {
+                let Some((next, distance)) = self.jump(maze, current, dir, &mut scan) else { continue };
+                let next_idx = maze.index(next);
+                let tentative_g = g_score + distance;
+                if tentative_g < best_g[next_idx] {
+                    if came_from[next_idx].is_none() {
+                        scan.visits.push(Visit { cell: next, kind: VisitKind::JumpPoint });
+                    }
+                    best_g[next_idx] = tentative_g;
+                    came_from[next_idx] = Some(current);
+                    arrived[next_idx] = Some(dir);
+                    open.push(AStarNode { f_score: tentative_g as f64 + estimate(next), g_score: tentative_g, position: next });
+                }
+            }
+            frontier_peak = frontier_peak.max(open.len());
+        }
+
+        // Jump points are linked in straight lines; fill in the cells in between.
+        let path = match came_from[maze.index(maze.end_point)] {
+            None => Vec::new(),
+            Some(_) => {
+                let mut path = vec![maze.end_point];
+                let mut current = maze.end_point;
+                while current != maze.start_point {
+                    let parent = came_from[maze.index(current)].expect("every jump point has a parent");
+                    let dir = heading_towards(current, parent);
+                    while current != parent {
+                        current = maze.neighbor(current, dir).expect("jumps stay inside the maze");
+                        path.push(current);
+                    }
+                }
+                path.reverse();
+                path
+            }
+        };
+        SearchOutcome {
+            expanded,
+            reexpanded: 0,
+            frontier_peak,
+            found: !path.is_empty(),
+            path,
+            visits: scan.visits,
+            looped: false,
+            gave_up: false,
+            elapsed: start_time.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_maze;
+
+    // Every solver except the plain wall followers, which can loop on mazes with loops.
+    fn solvers() -> Vec<(&'static str, Box<dyn Pathfinder>)> {
+        vec![
+            ("bfs", Box::new(Bfs)),
+            ("bidirectional bfs", Box::new(BidirectionalBfs)),
+            ("dfs", Box::new(Dfs)),
+            ("dijkstra", Box::new(Dijkstra)),
+            ("a*", Box::new(AStar { heuristic: Heuristic::Manhattan, weight: 1.0 })),
+            ("weighted a*", Box::new(AStar { heuristic: Heuristic::Manhattan, weight: 2.0 })),
+            ("greedy", Box::new(GreedyBestFirst { heuristic: Heuristic::Manhattan })),
+            ("iddfs", Box::new(Iddfs)),
+            ("ida*", Box::new(IdaStar { heuristic: Heuristic::Manhattan })),
+            ("jps", Box::new(JumpPointSearch)),
+            ("tremaux", Box::new(Tremaux)),
+            ("dead-end filling", Box::new(DeadEndFilling)),
+        ]
+    }
+
+    fn assert_valid_path(maze: &Maze, name: &str, path: &[(usize, usize)]) {
+        assert_eq!(path.first(), Some(&maze.start_point), "{} does not start at the start point", name);
+        assert_eq!(path.last(), Some(&maze.end_point), "{} does not end at the end point", name);
+        for step in path.windows(2) {
+            assert!(maze.open_neighbors(step[0]).any(|(_, next)| next == step[1]), "{} goes through a wall from {:?} to {:?}", name, step[0], step[1]);
+        }
+    }
+
+    // The cheapest start-to-end cost, by relaxing every cell until nothing changes. Slow, but
+    // shares no code with the solvers it checks.
+    fn cheapest_cost(maze: &Maze) -> usize {
+        let mut cost = vec![usize::MAX; maze.width * maze.height];
+        cost[maze.index(maze.start_point)] = 0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..cost.len() {
+                let position = maze.position(index);
+                for (_, next) in maze.open_neighbors(position) {
+                    let through = cost[maze.index(next)].saturating_add(maze.cost(position));
+                    if through < cost[index] {
+                        cost[index] = through;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        cost[maze.index(maze.end_point)]
+    }
+
+    #[test]
+    fn every_solver_finds_a_valid_path() {
+        for terrain in [false, true] {
+            let maze = test_maze(12, 10, terrain);
+            for (name, solver) in solvers() {
+                let outcome = solver.search(&maze);
+                assert!(outcome.found && !outcome.gave_up, "{} found no path", name);
+                assert_valid_path(&maze, name, &outcome.path);
+            }
+        }
+    }
+
+    #[test]
+    fn shortest_path_solvers_match_bfs() {
+        let maze = test_maze(20, 15, false);
+        let shortest = Bfs.search(&maze).path.len();
+        let solvers: [(&str, Box<dyn Pathfinder>); 4] = [
+            ("bidirectional bfs", Box::new(BidirectionalBfs)),
+            ("jps", Box::new(JumpPointSearch)),
+            ("iddfs", Box::new(Iddfs)),
+            ("a*", Box::new(AStar { heuristic: Heuristic::Manhattan, weight: 1.0 })),
+        ];
+        for (name, solver) in solvers {
+            assert_eq!(solver.search(&maze).path.len(), shortest, "{}", name);
+        }
+    }
+
+    #[test]
+    fn cheapest_path_solvers_match_on_terrain() {
+        let maze = test_maze(20, 15, true);
+        let cheapest = cheapest_cost(&maze);
+        let solvers: [(&str, Box<dyn Pathfinder>); 4] = [
+            ("dijkstra", Box::new(Dijkstra)),
+            ("a*", Box::new(AStar { heuristic: Heuristic::Manhattan, weight: 1.0 })),
+            ("a* euclidean", Box::new(AStar { heuristic: Heuristic::Euclidean, weight: 1.0 })),
+            ("ida*", Box::new(IdaStar { heuristic: Heuristic::Manhattan })),
+        ];
+        for (name, solver) in solvers {
+            let outcome = solver.search(&maze);
+            assert_valid_path(&maze, name, &outcome.path);
+            assert_eq!(maze.path_cost(&outcome.path), cheapest, "{}", name);
+        }
+    }
+
+    #[test]
+    fn wall_follower_loops_around_an_island() {
+        // An open 5x5 room: the end point in the middle touches no wall, so a hand on the outer
+        // wall only ever circles the border.
+        let mut maze = Maze::new(5, 5);
+        for index in 0..25 {
+            for dir in [1, 2] {
+                maze.set_wall(maze.position(index), dir, false);
+            }
+        }
+        maze.start_point = (0, 0);
+        maze.end_point = (2, 2);
+        for hand in [Hand::Left, Hand::Right] {
+            let outcome = WallFollower { hand }.search(&maze);
+            assert!(outcome.looped && !outcome.found);
+        }
+        assert!(Bfs.search(&maze).found);
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::pathfinding::SearchOutcome;
+use crate::render::Canvas;
+use crate::Maze;
+
+// Delay between animation frames, in GIF units of 10 ms.
+const FRAME_DELAY: u16 = 2;
+// How long the finished search stays on screen before the animation loops.
+const FINAL_HOLD_DELAY: u16 = 300;
+
+// Replays a search offscreen the way `Visualization::draw_search_animation` and `draw_path`
+// show it, writing a GIF frame at every `batch_size` boundary instead of updating a window.
+// Each frame only stores the rectangle that changed since the previous one.
+struct GifRecorder<'a> {
+    encoder: Encoder<BufWriter<File>>,
+    canvas: Canvas,
+    previous: Vec<u32>,
+    batch_size: usize,
+    path: &'a Path,
+}
+
+impl<'a> GifRecorder<'a> {
+    fn create(path: &'a Path, maze: &Maze, cell_size: usize, batch_size: usize) -> Result<Self, String> {
+        let mut canvas = Canvas::for_maze(maze.width, maze.height, cell_size);
+        canvas.draw_maze(maze);
+        let (width, height) = match (u16::try_from(canvas.width), u16::try_from(canvas.height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(format!("{}: {}x{} pixels is too large for a GIF", path.display(), canvas.width, canvas.height)),
+        };
+
+        let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut encoder = Encoder::new(BufWriter::new(file), width, height, &[])
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        encoder.set_repeat(Repeat::Infinite).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        let mut recorder = Self { encoder, previous: vec![!0; canvas.buffer.len()], canvas, batch_size, path };
+        recorder.write_frame(FRAME_DELAY)?;
+        Ok(recorder)
+    }
+
+    // Paints `items` one by one with `paint`, writing a frame after every `batch_size` of them
+    // and after the last.
+    fn record_cells<T>(&mut self, items: impl IntoIterator<Item = T>, mut paint: impl FnMut(&mut Canvas, T)) -> Result<(), String> {
+        let mut painted = 0;
+        for item in items {
+            paint(&mut self.canvas, item);
+            painted += 1;
+            if painted % self.batch_size == 0 {
+                self.write_frame(FRAME_DELAY)?;
+            }
+        }
+        self.write_frame(FRAME_DELAY)
+    }
+
+    // Writes the pixels that changed since the last frame. Does nothing if none did.
+    fn write_frame(&mut self, delay: u16) -> Result<(), String> {
+        let width = self.canvas.width;
+        let changed = |i: usize| self.canvas.buffer[i] != self.previous[i];
+        let Some(first) = (0..self.canvas.buffer.len()).find(|&i| changed(i)) else { return Ok(()) };
+        let last = (0..self.canvas.buffer.len()).rev().find(|&i| changed(i)).unwrap();
+        let (top, bottom) = (first / width, last / width);
+        let mut left = width;
+        let mut right = 0;
+        for y in top..=bottom {
+            for x in 0..width {
+                if changed(y * width + x) {
+                    left = left.min(x);
+                    right = right.max(x);
+                }
+            }
+        }
+        self.write_region(left, top, right - left + 1, bottom - top + 1, delay)
+    }
+
+    fn write_region(&mut self, left: usize, top: usize, width: usize, height: usize, delay: u16) -> Result<(), String> {
+        // The renderer only uses a handful of colours, so an exact local palette always fits
+        // in 256 entries; fall back to quantizing just in case.
+        let mut palette_index: HashMap<u32, u8> = HashMap::new();
+        let mut palette = Vec::new();
+        let mut indices = Vec::with_capacity(width * height);
+        let mut rgb = Vec::with_capacity(width * height * 3);
+        let mut too_many_colors = false;
+        for y in top..top + height {
+            let row = &self.canvas.buffer[y * self.canvas.width + left..y * self.canvas.width + left + width];
+            for &pixel in row {
+                let [_, r, g, b] = pixel.to_be_bytes();
+                rgb.extend_from_slice(&[r, g, b]);
+                match palette_index.get(&pixel) {
+                    Some(&index) => indices.push(index),
+                    None if palette_index.len() < 256 => {
+                        indices.push(palette_index.len() as u8);
+                        palette_index.insert(pixel, palette_index.len() as u8);
+                        palette.extend_from_slice(&[r, g, b]);
+                    }
+                    None => too_many_colors = true,
+                }
+            }
+        }
+
+        let mut frame = if !too_many_colors {
+            Frame::from_palette_pixels(width as u16, height as u16, indices, palette, None)
+        } else {
+            Frame::from_rgb_speed(width as u16, height as u16, &rgb, 10)
+        };
+        frame.left = left as u16;
+        frame.top = top as u16;
+        frame.delay = delay;
+        self.encoder.write_frame(&frame).map_err(|e| format!("{}: {}", self.path.display(), e))?;
+
+        for y in top..top + height {
+            let start = y * self.canvas.width + left;
+            self.previous[start..start + width].copy_from_slice(&self.canvas.buffer[start..start + width]);
+        }
+        Ok(())
+    }
+
+    // Keeps the final picture up for a while before the animation loops. GIF delays apply after
+    // a frame is shown, so this rewrites a single unchanged pixel with the long delay.
+    fn finish(mut self) -> Result<(), String> {
+        self.write_region(0, 0, 1, 1, FINAL_HOLD_DELAY)
+    }
+}
+
+// Records a finished search as an animated GIF: the visit events in `visit_colors` (indexed by
+// kind), then the path in `path_color`, `batch_size` cells per frame.
+pub fn record_search_gif(
+    path: &Path,
+    maze: &Maze,
+    outcome: &SearchOutcome,
+    visit_colors: [u32; 4],
+    path_color: u32,
+    cell_size: usize,
+    batch_size: usize,
+) -> Result<(), String> {
+    let mut recorder = GifRecorder::create(path, maze, cell_size, batch_size)?;
+    recorder.record_cells(outcome.visits.iter().copied(), |canvas, visit| canvas.draw_visit(visit, visit_colors))?;
+    recorder.record_cells(outcome.path.iter().copied(), |canvas, cell| canvas.fill_cell(cell, path_color))?;
+    recorder.canvas.draw_endpoints(maze);
+    recorder.write_frame(FRAME_DELAY)?;
+    recorder.finish()
+}
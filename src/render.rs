@@ -0,0 +1,215 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use font8x8::legacy::BASIC_LEGACY;
+use rayon::prelude::*;
+
+use crate::pathfinding::{SearchOutcome, Visit, VisitKind};
+use crate::Maze;
+
+pub const BACKGROUND_COLOR: u32 = 0x00101020;
+const WALL_COLOR: u32 = 0xFF808080;
+const START_COLOR: u32 = 0x0000FF00;
+const END_COLOR: u32 = 0x00FF0000;
+// Cells sealed off by a filling solver. Shared by every algorithm, like the wall colour.
+pub const FILLED_COLOR: u32 = 0x00383848;
+// Jump points of `JumpPointSearch`, bright so they stand out from the cells jumped over.
+pub const JUMP_POINT_COLOR: u32 = 0x00FFFFFF;
+
+// An offscreen pixel buffer with the maze laid out on it. The window shows one of these,
+// and the image exporters draw into their own without needing a display.
+// Pixels are 0x00RRGGBB; the top byte is ignored.
+pub struct Canvas {
+    pub buffer: Vec<u32>,
+    pub width: usize,
+    pub height: usize,
+    cell_size: usize,
+    offset_x: usize,
+    offset_y: usize,
+}
+
+impl Canvas {
+    // Picks the largest cell size that fits the maze on the screen with a 50px margin, then centres it.
+    pub fn fit_to_screen(screen_width: usize, screen_height: usize, maze_width: usize, maze_height: usize) -> Self {
+        let min_margin = 50;
+        let max_cell_width = (screen_width - 2 * min_margin) / maze_width;
+        let max_cell_height = (screen_height - 2 * min_margin) / maze_height;
+        let cell_size = max_cell_width.min(max_cell_height).max(1);
+
+        let maze_width_px = maze_width * cell_size;
+        let maze_height_px = maze_height * cell_size;
+        Self {
+            buffer: vec![0; screen_width * screen_height],
+            width: screen_width,
+            height: screen_height,
+            cell_size,
+            offset_x: (screen_width.saturating_sub(maze_width_px)) / 2,
+            offset_y: (screen_height.saturating_sub(maze_height_px)) / 2,
+        }
+    }
+
+    // A canvas exactly as large as the maze at `cell_size`, plus a one-cell border.
+    pub fn for_maze(maze_width: usize, maze_height: usize, cell_size: usize) -> Self {
+        let width = (maze_width + 2) * cell_size;
+        let height = (maze_height + 2) * cell_size;
+        Self {
+            buffer: vec![0; width * height],
+            width,
+            height,
+            cell_size,
+            offset_x: cell_size,
+            offset_y: cell_size,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.fill(BACKGROUND_COLOR);
+    }
+
+    pub fn draw_char(&mut self, x: usize, y: usize, c: char, color: u32) {
+        if let Some(bitmap) = BASIC_LEGACY.get(c as usize) {
+            for (row, bits) in bitmap.iter().enumerate() {
+                for col in 0..8 {
+                    if (bits >> col) & 1 == 1 {
+                        let px = x + col;
+                        let py = y + row;
+                        if px < self.width && py < self.height {
+                            self.buffer[py * self.width + px] = color;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: u32) {
+        for (i, c) in text.chars().enumerate() {
+            self.draw_char(x + i * 8, y, c, color);
+        }
+    }
+
+    // Clears the canvas, shades cells by terrain, draws every wall and marks the start and end points.
+    pub fn draw_maze(&mut self, maze: &Maze) {
+        self.clear();
+        let maze_width_px = maze.width * self.cell_size;
+        let maze_height_px = maze.height * self.cell_size;
+        let (cell_size, offset_x, offset_y) = (self.cell_size, self.offset_x, self.offset_y);
+
+        self.buffer
+            .par_chunks_mut(self.width)
+            .enumerate()
+            .for_each(|(y_pixel, row_slice)| {
+                if y_pixel < offset_y || y_pixel >= offset_y + maze_height_px { return; }
+                let y_cell = (y_pixel - offset_y) / cell_size;
+                if y_cell >= maze.height { return; }
+
+                for (x_pixel, pixel) in row_slice.iter_mut().enumerate() {
+                    if x_pixel < offset_x || x_pixel >= offset_x + maze_width_px { continue; }
+                    let x_cell = (x_pixel - offset_x) / cell_size;
+                    if x_cell >= maze.width { continue; }
+
+                    let inner_x = (x_pixel - offset_x) % cell_size;
+                    let inner_y = (y_pixel - offset_y) % cell_size;
+
+                    let walls = maze.walls((x_cell, y_cell));
+
+                    // A cell closed on every side can never be reached, e.g. a dark pixel of an
+                    // imported image, so it is drawn as solid wall.
+                    let mut is_wall = walls == [true; 4];
+                    if walls[0] && inner_y == 0 { is_wall = true; }
+                    if walls[1] && inner_x == cell_size - 1 { is_wall = true; }
+                    if walls[2] && inner_y == cell_size - 1 { is_wall = true; }
+                    if walls[3] && inner_x == 0 { is_wall = true; }
+
+                    if is_wall {
+                        *pixel = WALL_COLOR;
+                    } else if let Some(color) = maze.terrain((x_cell, y_cell)).color() {
+                        *pixel = color;
+                    }
+                }
+            });
+
+        self.draw_endpoints(maze);
+    }
+
+    pub fn draw_endpoints(&mut self, maze: &Maze) {
+        self.fill_cell(maze.start_point, START_COLOR);
+        self.fill_cell(maze.end_point, END_COLOR);
+    }
+
+    // Paints a square half the cell size in the middle of cell `(x, y)`.
+    pub fn fill_cell(&mut self, (x, y): (usize, usize), color: u32) {
+        let path_size = (self.cell_size / 2).max(1);
+        let path_offset = (self.cell_size - path_size) / 2;
+        for dy in 0..path_size {
+            for dx in 0..path_size {
+                let px = self.offset_x + x * self.cell_size + path_offset + dx;
+                let py = self.offset_y + y * self.cell_size + path_offset + dy;
+                if px < self.width && py < self.height {
+                    self.buffer[py * self.width + px] = color;
+                }
+            }
+        }
+    }
+
+    // Paints all of cell `(x, y)` inside its wall lines, so filled-in dead ends read as solid
+    // blocks. Cells too small to have an inside fall back to `fill_cell`.
+    pub fn seal_cell(&mut self, (x, y): (usize, usize), color: u32) {
+        if self.cell_size < 3 {
+            return self.fill_cell((x, y), color);
+        }
+        for dy in 1..self.cell_size - 1 {
+            for dx in 1..self.cell_size - 1 {
+                let px = self.offset_x + x * self.cell_size + dx;
+                let py = self.offset_y + y * self.cell_size + dy;
+                if px < self.width && py < self.height {
+                    self.buffer[py * self.width + px] = color;
+                }
+            }
+        }
+    }
+
+    // Draws one visit event in `colors`, indexed by its `VisitKind`.
+    pub fn draw_visit(&mut self, visit: Visit, colors: [u32; 4]) {
+        let color = colors[visit.kind as usize];
+        match visit.kind {
+            VisitKind::Filled => self.seal_cell(visit.cell, color),
+            VisitKind::Discovered | VisitKind::DiscoveredFromEnd | VisitKind::JumpPoint => self.fill_cell(visit.cell, color),
+        }
+    }
+
+    // Packs the buffer into 8-bit RGB, dropping the unused top byte of each pixel.
+    pub fn to_rgb(&self) -> Vec<u8> {
+        self.buffer
+            .iter()
+            .flat_map(|&pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+            .collect()
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| format!("{}: {}", path.display(), e))?;
+        writer
+            .write_image_data(&self.to_rgb())
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+// Draws one finished search offscreen: the maze, every visit event (in `visit_colors`, indexed
+// by kind), then the final path, with the start and end markers kept on top.
+pub fn render_outcome(maze: &Maze, outcome: &SearchOutcome, visit_colors: [u32; 4], path_color: u32, cell_size: usize) -> Canvas {
+    let mut canvas = Canvas::for_maze(maze.width, maze.height, cell_size);
+    canvas.draw_maze(maze);
+    for &visit in &outcome.visits {
+        canvas.draw_visit(visit, visit_colors);
+    }
+    for &cell in &outcome.path {
+        canvas.fill_cell(cell, path_color);
+    }
+    canvas.draw_endpoints(maze);
+    canvas
+}
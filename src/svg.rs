@@ -0,0 +1,233 @@
+use std::fmt::Write as _;
+
+use crate::pathfinding::{Visit, VisitKind};
+use crate::Maze;
+
+// SVG user units per maze cell. The output scales freely, this only sets the wall thickness ratio.
+const CELL_SIZE: usize = 10;
+const WALL_COLOR: u32 = 0x808080;
+const START_COLOR: u32 = 0x00FF00;
+const END_COLOR: u32 = 0xFF0000;
+
+// Optional layers drawn on top of the walls.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum SvgLayer {
+    // Start and end point markers.
+    Markers,
+    // Every cell a search discovered or filled in.
+    Explored,
+    // The final path as one polyline.
+    Path,
+}
+
+// A search result to draw in the `Explored` and `Path` layers.
+pub struct SvgRun<'a> {
+    pub visits: &'a [Visit],
+    pub path: &'a [(usize, usize)],
+    // Colours for each visit event, indexed by `VisitKind`.
+    pub visit_colors: [u32; 4],
+    pub path_color: u32,
+}
+
+fn hex(color: u32) -> String {
+    format!("#{:06x}", color & 0x00FF_FFFF)
+}
+
+// Horizontal wall runs as `(y, x_start, x_end)` in cell units. Neighbouring walls on the same
+// grid line are merged into one segment, which keeps files small on long corridors.
+fn horizontal_walls(maze: &Maze) -> Vec<(usize, usize, usize)> {
+    let mut segments = Vec::new();
+    for y in 0..=maze.height {
+        let mut run_start = None;
+        for x in 0..=maze.width {
+            let has_wall = x < maze.width
+                && if y < maze.height { maze.has_wall((x, y), 0) } else { maze.has_wall((x, y - 1), 2) };
+            match (has_wall, run_start) {
+                (true, None) => run_start = Some(x),
+                (false, Some(start)) => {
+                    segments.push((y, start, x));
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    segments
+}
+
+// Vertical wall runs as `(x, y_start, y_end)` in cell units, merged like `horizontal_walls`.
+fn vertical_walls(maze: &Maze) -> Vec<(usize, usize, usize)> {
+    let mut segments = Vec::new();
+    for x in 0..=maze.width {
+        let mut run_start = None;
+        for y in 0..=maze.height {
+            let has_wall = y < maze.height
+                && if x < maze.width { maze.has_wall((x, y), 3) } else { maze.has_wall((x - 1, y), 1) };
+            match (has_wall, run_start) {
+                (true, None) => run_start = Some(y),
+                (false, Some(start)) => {
+                    segments.push((x, start, y));
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    segments
+}
+
+fn cell_center(position: (usize, usize)) -> (usize, usize) {
+    (position.0 * CELL_SIZE + CELL_SIZE / 2, position.1 * CELL_SIZE + CELL_SIZE / 2)
+}
+
+// Builds an SVG document with the maze walls and whichever of `layers` apply. The
+// `Explored` and `Path` layers are skipped when `run` is `None`.
+pub fn maze_to_svg(maze: &Maze, layers: &[SvgLayer], run: Option<&SvgRun>) -> String {
+    let width = maze.width * CELL_SIZE;
+    let height = maze.height * CELL_SIZE;
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -1 {} {}" width="{}" height="{}">"#,
+        width + 2,
+        height + 2,
+        width + 2,
+        height + 2
+    ).unwrap();
+
+    if let Some(run) = run.filter(|_| layers.contains(&SvgLayer::Explored)) {
+        // One group per kind of visit, skipping kinds the search never produced.
+        let groups = [
+            ("explored", VisitKind::Discovered),
+            ("explored-from-end", VisitKind::DiscoveredFromEnd),
+            ("filled", VisitKind::Filled),
+            ("jump-points", VisitKind::JumpPoint),
+        ];
+        for (group, kind) in groups {
+            if !run.visits.iter().any(|visit| visit.kind == kind) {
+                continue;
+            }
+            writeln!(
+                svg,
+                r#"<g id="{}" fill="{}" fill-opacity="0.5">"#,
+                group,
+                hex(run.visit_colors[kind as usize])
+            ).unwrap();
+            for &(x, y) in run.visits.iter().filter(|visit| visit.kind == kind).map(|visit| &visit.cell) {
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+                    x * CELL_SIZE,
+                    y * CELL_SIZE,
+                    CELL_SIZE,
+                    CELL_SIZE
+                ).unwrap();
+            }
+            writeln!(svg, "</g>").unwrap();
+        }
+    }
+
+    writeln!(
+        svg,
+        r#"<g id="walls" stroke="{}" stroke-width="1" stroke-linecap="square">"#,
+        hex(WALL_COLOR)
+    ).unwrap();
+    for (y, x1, x2) in horizontal_walls(maze) {
+        writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+            x1 * CELL_SIZE,
+            y * CELL_SIZE,
+            x2 * CELL_SIZE,
+            y * CELL_SIZE
+        ).unwrap();
+    }
+    for (x, y1, y2) in vertical_walls(maze) {
+        writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+            x * CELL_SIZE,
+            y1 * CELL_SIZE,
+            x * CELL_SIZE,
+            y2 * CELL_SIZE
+        ).unwrap();
+    }
+    writeln!(svg, "</g>").unwrap();
+
+    if let Some(run) = run.filter(|run| layers.contains(&SvgLayer::Path) && !run.path.is_empty()) {
+        let points: Vec<String> = run
+            .path
+            .iter()
+            .map(|&cell| {
+                let (cx, cy) = cell_center(cell);
+                format!("{},{}", cx, cy)
+            })
+            .collect();
+        writeln!(
+            svg,
+            r#"<polyline id="path" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round" points="{}"/>"#,
+            hex(run.path_color),
+            CELL_SIZE / 3,
+            points.join(" ")
+        ).unwrap();
+    }
+
+    if layers.contains(&SvgLayer::Markers) {
+        writeln!(svg, r#"<g id="markers">"#).unwrap();
+        for (position, color) in [(maze.start_point, START_COLOR), (maze.end_point, END_COLOR)] {
+            let (cx, cy) = cell_center(position);
+            writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                cx,
+                cy,
+                CELL_SIZE / 3,
+                hex(color)
+            ).unwrap();
+        }
+        writeln!(svg, "</g>").unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 4x3 maze with every interior wall removed.
+    fn open_room() -> Maze {
+        let mut maze = Maze::new(4, 3);
+        for y in 0..3 {
+            for x in 0..4 {
+                maze.set_wall((x, y), 1, false);
+                maze.set_wall((x, y), 2, false);
+            }
+        }
+        maze
+    }
+
+    #[test]
+    fn border_is_four_segments() {
+        let maze = open_room();
+        assert_eq!(horizontal_walls(&maze), [(0, 0, 4), (3, 0, 4)]);
+        assert_eq!(vertical_walls(&maze), [(0, 0, 3), (4, 0, 3)]);
+        assert_eq!(maze_to_svg(&maze, &[], None).matches("<line").count(), 4);
+    }
+
+    #[test]
+    fn merges_neighbouring_walls() {
+        let mut maze = open_room();
+        // An L of three walls: under (1, 0) and (2, 0), then down the east side of (2, 1).
+        maze.set_wall((1, 0), 2, true);
+        maze.set_wall((2, 0), 2, true);
+        maze.set_wall((2, 1), 1, true);
+        assert_eq!(horizontal_walls(&maze), [(0, 0, 4), (1, 1, 3), (3, 0, 4)]);
+        assert_eq!(vertical_walls(&maze), [(0, 0, 3), (3, 1, 2), (4, 0, 3)]);
+        // A closed maze has one segment per grid line.
+        let closed = Maze::new(4, 3);
+        assert_eq!(horizontal_walls(&closed).len() + vertical_walls(&closed).len(), 4 + 5);
+    }
+}
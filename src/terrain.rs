@@ -0,0 +1,98 @@
+use rand::{Rng, RngCore};
+
+use crate::Maze;
+
+// What a cell is covered with. Entering a cell costs its terrain's `cost`, so once a maze has
+// terrain the fewest-hops path (what BFS finds) is no longer necessarily the cheapest one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Terrain {
+    Road,
+    Mud,
+    Water,
+}
+
+impl Terrain {
+    pub fn cost(self) -> usize {
+        match self {
+            Terrain::Road => 1,
+            Terrain::Mud => 3,
+            Terrain::Water => 8,
+        }
+    }
+
+    // Background tint in `draw_maze`. Road keeps the plain background.
+    pub fn color(self) -> Option<u32> {
+        match self {
+            Terrain::Road => None,
+            Terrain::Mud => Some(0x003A2A14),
+            Terrain::Water => Some(0x00102A5A),
+        }
+    }
+
+    // Two-bit code used by the maze file formats.
+    pub fn code(self) -> u8 {
+        match self {
+            Terrain::Road => 0,
+            Terrain::Mud => 1,
+            Terrain::Water => 2,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Terrain::Road),
+            1 => Some(Terrain::Mud),
+            2 => Some(Terrain::Water),
+            _ => None,
+        }
+    }
+}
+
+// Lattice spacing of the coarsest noise octave, in cells. Sets the typical size of a region.
+const NOISE_SCALE: f64 = 16.0;
+
+// Smooth value noise: random values on a lattice, blended with a smoothstep. Two octaves give
+// blobs with ragged edges instead of perfect circles.
+struct ValueNoise {
+    lattice: Vec<f64>,
+    columns: usize,
+}
+
+impl ValueNoise {
+    fn new(width: usize, height: usize, scale: f64, rng: &mut dyn RngCore) -> Self {
+        let columns = (width as f64 / scale) as usize + 2;
+        let rows = (height as f64 / scale) as usize + 2;
+        let lattice = (0..columns * rows).map(|_| rng.random::<f64>()).collect();
+        Self { lattice, columns }
+    }
+
+    fn sample(&self, x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (smooth(x.fract()), smooth(y.fract()));
+        let at = |cx: usize, cy: usize| self.lattice[cy * self.columns + cx];
+        let top = at(x0, y0) + (at(x0 + 1, y0) - at(x0, y0)) * tx;
+        let bottom = at(x0, y0 + 1) + (at(x0 + 1, y0 + 1) - at(x0, y0 + 1)) * tx;
+        top + (bottom - top) * ty
+    }
+}
+
+// Covers `maze` with noise-shaped regions of mud and water, leaving the rest as road. The
+// lowest areas of the noise become water, with a band of mud around them.
+pub fn paint(maze: &mut Maze, rng: &mut dyn RngCore) {
+    let coarse = ValueNoise::new(maze.width, maze.height, NOISE_SCALE, rng);
+    let fine = ValueNoise::new(maze.width, maze.height, NOISE_SCALE / 4.0, rng);
+    for y in 0..maze.height {
+        for x in 0..maze.width {
+            let (fx, fy) = (x as f64, y as f64);
+            let height = 0.75 * coarse.sample(fx / NOISE_SCALE, fy / NOISE_SCALE)
+                + 0.25 * fine.sample(fx * 4.0 / NOISE_SCALE, fy * 4.0 / NOISE_SCALE);
+            let terrain = match height {
+                h if h < 0.3 => Terrain::Water,
+                h if h < 0.45 => Terrain::Mud,
+                _ => Terrain::Road,
+            };
+            maze.set_terrain((x, y), terrain);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::terrain::Terrain;
+use crate::Maze;
+
+// Plain-text maze files: a `key value` header followed by ASCII art, one `+` per grid corner.
+//
+// ```text
+// # knossos maze v1
+// width 3
+// height 2
+// start 0 0
+// end 2 1
+// seed 42
+// +--+--+--+
+// |S    |  |
+// +  +--+  +
+// |       E|
+// +--+--+--+
+// ```
+//
+// `seed` is optional. Cell interiors show terrain, `..` for mud and `~~` for water (see
+// `terrain::Terrain`); anything else is road. The `S`/`E` markers are only there to make
+// hand-editing easier. The outer border is always closed, whatever the file says.
+const MAGIC: &str = "# knossos maze v1";
+
+impl Maze {
+    pub fn to_text(&self, seed: Option<u64>) -> String {
+        let mut text = String::new();
+        writeln!(text, "{}", MAGIC).unwrap();
+        writeln!(text, "width {}", self.width).unwrap();
+        writeln!(text, "height {}", self.height).unwrap();
+        writeln!(text, "start {} {}", self.start_point.0, self.start_point.1).unwrap();
+        writeln!(text, "end {} {}", self.end_point.0, self.end_point.1).unwrap();
+        if let Some(seed) = seed {
+            writeln!(text, "seed {}", seed).unwrap();
+        }
+
+        for y in 0..self.height {
+            text.push('+');
+            for x in 0..self.width {
+                text.push_str(if self.has_wall((x, y), 0) { "--+" } else { "  +" });
+            }
+            text.push('\n');
+
+            text.push(if self.has_wall((0, y), 3) { '|' } else { ' ' });
+            for x in 0..self.width {
+                let ground = match self.terrain((x, y)) {
+                    Terrain::Road => ' ',
+                    Terrain::Mud => '.',
+                    Terrain::Water => '~',
+                };
+                let interior = match (x, y) {
+                    p if p == self.start_point => ['S', ground],
+                    p if p == self.end_point => [ground, 'E'],
+                    _ => [ground, ground],
+                };
+                text.extend(interior);
+                text.push(if self.has_wall((x, y), 1) { '|' } else { ' ' });
+            }
+            text.push('\n');
+        }
+
+        text.push('+');
+        for x in 0..self.width {
+            text.push_str(if self.has_wall((x, self.height - 1), 2) { "--+" } else { "  +" });
+        }
+        text.push('\n');
+        text
+    }
+
+    // Parses the format written by `to_text`, returning the maze and its seed if the header has one.
+    // Errors name the offending line.
+    pub fn from_text(text: &str) -> Result<(Maze, Option<u64>), String> {
+        let mut lines = text.lines().enumerate().peekable();
+        match lines.next() {
+            Some((_, line)) if line.trim_end() == MAGIC => {}
+            _ => return Err(format!("line 1: expected `{}`", MAGIC)),
+        }
+
+        let (mut width, mut height, mut start, mut end, mut seed) = (None, None, None, None, None);
+        while let Some(&(number, line)) = lines.peek() {
+            let line = line.trim();
+            if line.starts_with('+') {
+                break;
+            }
+            lines.next();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line_error = |message: &str| format!("line {}: {}", number + 1, message);
+            let parse = |value: &str| value.parse::<usize>().map_err(|_| line_error("expected a non-negative number"));
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                ["width", value] => width = Some(parse(value)?),
+                ["height", value] => height = Some(parse(value)?),
+                ["start", x, y] => start = Some((parse(x)?, parse(y)?)),
+                ["end", x, y] => end = Some((parse(x)?, parse(y)?)),
+                ["seed", value] => seed = Some(value.parse::<u64>().map_err(|_| line_error("expected a seed"))?),
+                _ => return Err(line_error(&format!("unknown header line `{}`", line))),
+            }
+        }
+
+        let width = width.filter(|&w| w > 0).ok_or("header needs `width` of at least 1")?;
+        let height = height.filter(|&h| h > 0).ok_or("header needs `height` of at least 1")?;
+        let too_large = || format!("maze size {}x{} is too large", width, height);
+        width.checked_mul(height).and_then(|cells| cells.checked_mul(2)).ok_or_else(too_large)?;
+        let row_len = width.checked_mul(3).and_then(|len| len.checked_add(1)).ok_or_else(too_large)?;
+        let row_count = height.checked_mul(2).and_then(|count| count.checked_add(1)).ok_or_else(too_large)?;
+        for (name, point) in [("start", start), ("end", end)] {
+            if let Some((x, y)) = point
+                && (x >= width || y >= height)
+            {
+                return Err(format!("`{}` {} {} is outside the {}x{} maze", name, x, y, width, height));
+            }
+        }
+
+        // The art has to be all there before the maze is allocated, so a header alone cannot
+        // ask for gigabytes. Rows of corners end in `+` and keep their full length; hand-edited
+        // files may lose trailing spaces on the others, so those are padded back out.
+        let rows: Vec<(usize, &str)> = lines.take(row_count).collect();
+        if rows.len() != row_count {
+            return Err(format!("expected {} rows of maze art, found {}", row_count, rows.len()));
+        }
+        for &(number, line) in rows.iter().step_by(2) {
+            if line.trim_end().len() != row_len {
+                return Err(format!("line {}: expected {} characters of maze art", number + 1, row_len));
+            }
+        }
+        let rows: Vec<(usize, Vec<u8>)> = rows
+            .into_iter()
+            .map(|(number, line)| {
+                let mut row = line.as_bytes().to_vec();
+                row.resize(row_len.max(row.len()), b' ');
+                (number, row)
+            })
+            .collect();
+
+        let mut maze = Maze::new(width, height);
+        maze.start_point = start.unwrap_or(maze.start_point);
+        maze.end_point = end.unwrap_or(maze.end_point);
+
+        for y in 0..=height {
+            let (number, row) = &rows[2 * y];
+            for x in 0..width {
+                let wall = match &row[3 * x + 1..3 * x + 3] {
+                    b"--" => true,
+                    b"  " => false,
+                    _ => return Err(format!("line {}: expected `--` or two spaces at column {}", number + 1, 3 * x + 2)),
+                };
+                if y > 0 {
+                    maze.set_wall((x, y - 1), 2, wall);
+                }
+            }
+        }
+        for y in 0..height {
+            let (number, row) = &rows[2 * y + 1];
+            for x in 0..=width {
+                let wall = match row[3 * x] {
+                    b'|' => true,
+                    b' ' => false,
+                    _ => return Err(format!("line {}: expected `|` or a space at column {}", number + 1, 3 * x + 1)),
+                };
+                if x > 0 {
+                    maze.set_wall((x - 1, y), 1, wall);
+                }
+                if x < width {
+                    let terrain = match &row[3 * x + 1..3 * x + 3] {
+                        interior if interior.contains(&b'~') => Terrain::Water,
+                        interior if interior.contains(&b'.') => Terrain::Mud,
+                        _ => Terrain::Road,
+                    };
+                    if terrain != Terrain::Road {
+                        maze.set_terrain((x, y), terrain);
+                    }
+                }
+            }
+        }
+
+        Ok((maze, seed))
+    }
+}
+
+// Reads a maze file written by `save` (or by hand), returning the maze and its seed if recorded.
+pub fn load(path: &Path) -> Result<(Maze, Option<u64>), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    Maze::from_text(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn save(path: &Path, maze: &Maze, seed: Option<u64>) -> Result<(), String> {
+    fs::write(path, maze.to_text(seed)).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_maze, Maze};
+
+    fn parse_error(text: &str) -> String {
+        Maze::from_text(text).err().expect("the maze was accepted")
+    }
+
+    #[test]
+    fn round_trips_walls_terrain_endpoints_and_seed() {
+        let maze = test_maze(23, 17, true);
+        let text = maze.to_text(Some(42));
+        let (loaded, seed) = Maze::from_text(&text).unwrap();
+        assert_eq!(seed, Some(42));
+        assert_eq!((loaded.width, loaded.height), (23, 17));
+        assert_eq!((loaded.start_point, loaded.end_point), (maze.start_point, maze.end_point));
+        assert!(loaded.is_weighted());
+        assert_eq!(loaded.to_text(Some(42)), text);
+    }
+
+    #[test]
+    fn round_trips_without_seed_or_terrain() {
+        let maze = test_maze(5, 3, false);
+        let (loaded, seed) = Maze::from_text(&maze.to_text(None)).unwrap();
+        assert_eq!(seed, None);
+        assert!(!loaded.is_weighted());
+        assert_eq!(loaded.to_text(None), maze.to_text(None));
+    }
+
+    #[test]
+    fn accepts_rows_with_trailing_spaces_trimmed() {
+        let text = test_maze(6, 4, false).to_text(None);
+        let trimmed: String = text.lines().map(|line| format!("{}\n", line.trim_end())).collect();
+        assert_eq!(Maze::from_text(&trimmed).unwrap().0.to_text(None), text);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let text = test_maze(4, 3, false).to_text(None).replacen("maze v1", "maze v2", 1);
+        assert!(parse_error(&text).starts_with("line 1:"));
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let text = test_maze(4, 3, false).to_text(None);
+        let truncated: String = text.lines().take(text.lines().count() - 1).map(|line| format!("{}\n", line)).collect();
+        assert_eq!(parse_error(&truncated), "expected 7 rows of maze art, found 6");
+    }
+
+    #[test]
+    fn rejects_huge_sizes_before_allocating() {
+        let header = |width: usize, height: usize| format!("# knossos maze v1\nwidth {}\nheight {}\n+--+\n", width, height);
+        assert_eq!(parse_error(&header(1 << 32, (1 << 32) + 1)), "maze size 4294967296x4294967297 is too large");
+        assert_eq!(parse_error(&header(usize::MAX, 1)), format!("maze size {}x1 is too large", usize::MAX));
+        assert_eq!(parse_error(&header(200000, 200000)), "expected 400001 rows of maze art, found 1");
+        // Enough rows, but far too short for the width in the header.
+        let text = "# knossos maze v1\nwidth 1000000000\nheight 1\n+--+\n|  |\n+--+\n";
+        assert_eq!(parse_error(text), "line 4: expected 3000000001 characters of maze art");
+    }
+
+    #[test]
+    fn rejects_a_corrupted_wall_and_names_its_line() {
+        let text = test_maze(4, 3, false).to_text(None);
+        // The top border is line 6, after the five header lines.
+        let corrupted = text.replacen("+--", "+-x", 1);
+        assert_eq!(parse_error(&corrupted), "line 6: expected `--` or two spaces at column 2");
+    }
+
+    #[test]
+    fn rejects_endpoints_outside_the_maze() {
+        let text = test_maze(4, 3, false).to_text(None).replacen("end 2 2", "end 4 2", 1);
+        assert_eq!(parse_error(&text), "`end` 4 2 is outside the 4x3 maze");
+    }
+}